@@ -1,16 +1,51 @@
-use super::parse::CallDef;
+use super::parse::{CallDef, CallerKind, ReceiverKind};
 use quote::quote;
 
 /// See the `fn call` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
-	let CallDef { pallet_struct, methods } = def;
+	let CallDef { pallet_struct, where_clause, methods } = def;
 
 	// This is a vector of all the callable function names.
 	let fn_name = methods.iter().map(|method| &method.name).collect::<Vec<_>>();
 
+	// This is a vector of the first argument passed to each of the functions in `fn_name`. Functions
+	// taking an `origin` get it as is, while functions taking a `caller` only accept signed origins.
+	let caller_arg = methods
+		.iter()
+		.map(|method| match method.caller_kind {
+			CallerKind::Caller => quote! { crate::system::ensure_signed(origin)? },
+			CallerKind::Origin => quote! { origin },
+		})
+		.collect::<Vec<_>>();
+
+	// This is a vector of how each of the functions in `fn_name` is reached. Functions taking `self`
+	// are called on the pallet, which we get out of the runtime, while functions taking the
+	// `runtime` are given the runtime itself.
+	let fn_call = methods
+		.iter()
+		.map(|method| {
+			let name = &method.name;
+			match method.receiver_kind {
+				ReceiverKind::Pallet => quote! {
+					core::convert::AsMut::<#pallet_struct<T>>::as_mut(runtime).#name
+				},
+				ReceiverKind::Runtime => quote! { #pallet_struct::<T>::#name },
+			}
+		})
+		.collect::<Vec<_>>();
+
+	// Functions taking the `runtime` get it as their first argument.
+	let runtime_arg = methods
+		.iter()
+		.map(|method| match method.receiver_kind {
+			ReceiverKind::Pallet => quote! {},
+			ReceiverKind::Runtime => quote! { runtime, },
+		})
+		.collect::<Vec<_>>();
+
 	// This is a nested vector of all the arguments for each of the functions in `fn_name`. It does
-	// not include the `self` or `caller: T::AccountId` parameter, which we always assume are the
-	// first two parameters to these calls.
+	// not include the `self`/`runtime` or `caller`/`origin` parameter, which we always assume are
+	// the first two parameters to these calls.
 	let args_name = methods
 		.iter()
 		.map(|method| method.args.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>())
@@ -23,40 +58,96 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		.map(|method| method.args.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>())
 		.collect::<Vec<_>>();
 
+	// This is a vector of the index of each of the functions in `fn_name`, which calls are encoded
+	// with.
+	let fn_index = (0..methods.len()).map(|index| index as u8).collect::<Vec<_>>();
+
+	// The bounds of the `impl` block also apply to the dispatch logic, since it calls the functions.
+	let where_predicates = where_clause.map(|clause| clause.predicates).unwrap_or_default();
+
 	// This quote block creates an `enum Call` which contains all the calls exposed by our pallet,
-	// and the `Dispatch` trait logic to route a `caller` to access those functions.
+	// the `Dispatchable` trait logic to route an `origin` to access those functions, and the names
+	// and encoding of the calls.
 	let dispatch_impl = quote! {
 		// The callable functions exposed by this pallet.
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
+		#[derive(Clone, Debug)]
 		#[allow(non_camel_case_types)]
 		pub enum Call<T: Config> {
 			#(
 				#fn_name { #( #args_name: #args_type),* },
 			)*
+			// Not a real call. This keeps `T` in use for pallets whose calls do not mention it, and
+			// can never be constructed.
+			#[doc(hidden)]
+			__phantom(core::marker::PhantomData<T>, core::convert::Infallible),
 		}
 
 		// Dispatch logic at the pallet level, mapping each of the items in the `Call` enum to the
-		// appropriate function call with all arguments, including the `caller`.
-		impl<T: Config> crate::support::Dispatch for #pallet_struct<T> {
-			type Caller = T::AccountId;
-			type Call = Call<T>;
+		// appropriate function call with all arguments, including the `caller` or `origin`.
+		//
+		// The runtime `T` must give access to this pallet, which `macros::runtime` takes care of.
+		impl<T: Config> crate::support::Dispatchable<T> for Call<T>
+		where
+			T: core::convert::AsMut<#pallet_struct<T>>,
+			#where_predicates
+		{
+			type Origin = T::RuntimeOrigin;
 
-			fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
-				match call {
+			fn dispatch(self, runtime: &mut T, origin: Self::Origin) -> crate::support::DispatchResult {
+				match self {
 					#(
 						Call::#fn_name { #( #args_name ),* } => {
-							self.#fn_name(
-								// Note that we assume the first argument of every call is the `caller`.
-								caller,
+							#fn_call(
+								#runtime_arg
+								// Note that we assume the first argument of every call is the `caller`
+								// or `origin`.
+								#caller_arg,
 								#( #args_name ),*
 							)?;
 						},
 					)*
+					Call::__phantom(_, never) => match never {},
 				}
 				Ok(())
 			}
 		}
+
+		// The name of each call is the name of its function, e.g. for filtering calls by name.
+		impl<T: Config> crate::support::GetCallName for Call<T> {
+			fn call_name(&self) -> &'static str {
+				match self {
+					#(
+						Call::#fn_name { .. } => stringify!(#fn_name),
+					)*
+					Call::__phantom(_, never) => match *never {},
+				}
+			}
+
+			fn call_names() -> &'static [&'static str] {
+				&[ #( stringify!(#fn_name) ),* ]
+			}
+		}
+
+		// A call is encoded as the index of its function, followed by its arguments in order, e.g.
+		// for hashing it. This is only available when all the arguments can be encoded.
+		impl<T: Config> crate::support::Encode for Call<T>
+		where
+			#( #( #args_type: crate::support::Encode, )* )*
+		{
+			fn encode_to(&self, buffer: &mut Vec<u8>) {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => {
+							buffer.push(#fn_index);
+							#( crate::support::Encode::encode_to(#args_name, buffer); )*
+						},
+					)*
+					Call::__phantom(_, never) => match *never {},
+				}
+			}
+		}
 	};
 
 	// Return the generated code.
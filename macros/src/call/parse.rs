@@ -5,6 +5,7 @@ use syn::spanned::Spanned;
 mod keyword {
 	syn::custom_keyword!(T);
 	syn::custom_keyword!(AccountId);
+	syn::custom_keyword!(RuntimeOrigin);
 }
 
 /// This object will collect all the information we need to keep while parsing the callable
@@ -14,15 +15,41 @@ pub struct CallDef {
 	/// This is the name of the pallet struct where the callable functions are implemented. We
 	/// mostly assume it is `Pallet`.
 	pub pallet_struct: syn::Ident,
+	/// This is the `where` clause of the `impl` block, which also applies to the generated
+	/// dispatch logic.
+	pub where_clause: Option<syn::WhereClause>,
 	/// This is a list of the callable functions exposed by this pallet. See `CallVariantDef`.
 	pub methods: Vec<CallVariantDef>,
 }
 
+/// The first parameter of a callable function, which says what state it can access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiverKind {
+	/// A variant of `self`, so the function can only access the state of its own pallet.
+	Pallet,
+	/// `runtime: &mut T`, so the function can access the whole runtime, e.g. to dispatch other
+	/// calls. The state of its own pallet is then reached through `AsMut<Pallet<T>>`.
+	Runtime,
+}
+
+/// The second parameter of a callable function, which says who is calling it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallerKind {
+	/// `caller: T::AccountId`, which only accepts calls from a signed account.
+	Caller,
+	/// `origin: T::RuntimeOrigin`, which accepts any origin and lets the function check it.
+	Origin,
+}
+
 /// This is the metadata we keep about each callable function in our pallet.
 #[derive(Debug)]
 pub struct CallVariantDef {
 	/// The function name.
 	pub name: syn::Ident,
+	/// Whether the function takes `self` or the `runtime`.
+	pub receiver_kind: ReceiverKind,
+	/// Whether the function takes a `caller` or an `origin`.
+	pub caller_kind: CallerKind,
 	/// Information on args of the function: `(name, type)`.
 	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
 }
@@ -43,6 +70,9 @@ impl CallDef {
 			_ => panic!("not supported tokens"),
 		};
 
+		// Keep the `where` clause, since the callable functions may rely on it.
+		let where_clause = item_impl.generics.where_clause.clone();
+
 		// Here is where we will store all the callable functions.
 		let mut methods = vec![];
 		for item in item_impl.items {
@@ -50,31 +80,38 @@ impl CallDef {
 				// Here is where we will store all the args for each callable functions.
 				let mut args = vec![];
 
-				// First argument should be some variant of `self`.
-				match method.sig.inputs.first() {
-					Some(syn::FnArg::Receiver(_)) => {},
+				// First argument should be some variant of `self`, or `runtime: &mut T`.
+				let receiver_kind = match method.sig.inputs.first() {
+					Some(syn::FnArg::Receiver(_)) => ReceiverKind::Pallet,
+					Some(syn::FnArg::Typed(arg)) => {
+						check_runtime_arg(arg)?;
+						ReceiverKind::Runtime
+					},
 					_ => {
-						let msg = "Invalid call, first argument must be a variant of self";
+						let msg = "Invalid call, first argument must be a variant of self or \
+							`runtime: &mut T`";
 						return Err(syn::Error::new(method.sig.span(), msg))
 					},
-				}
+				};
 
-				// The second argument should be the `caller: T::AccountId` argument.
-				match method.sig.inputs.iter().skip(1).next() {
+				// The second argument should be the `caller: T::AccountId` or the
+				// `origin: T::RuntimeOrigin` argument.
+				let caller_kind = match method.sig.inputs.iter().skip(1).next() {
 					Some(syn::FnArg::Typed(arg)) => {
 						// Here we specifically check that this argument is as we expect for
-						// `caller: T::AccountId`.
-						check_caller_arg(arg)?;
+						// `caller: T::AccountId` or `origin: T::RuntimeOrigin`.
+						check_caller_arg(arg)?
 					},
 					_ => {
-						let msg = "Invalid call, second argument should be `caller: T::AccountId`";
+						let msg = "Invalid call, second argument should be `caller: T::AccountId` \
+							or `origin: T::RuntimeOrigin`";
 						return Err(syn::Error::new(method.sig.span(), msg))
 					},
-				}
+				};
 
 				let fn_name = method.sig.ident.clone();
 
-				// Parsing the rest of the args. Skipping 2 for `self` and `caller`.
+				// Parsing the rest of the args. Skipping 2 for `self`/`runtime` and `caller`/`origin`.
 				for arg in method.sig.inputs.iter().skip(2) {
 					// All arguments should be typed.
 					let arg = if let syn::FnArg::Typed(arg) = arg {
@@ -96,19 +133,54 @@ impl CallDef {
 				}
 
 				// Store all the function name and the arg data for the function.
-				methods.push(CallVariantDef { name: fn_name, args });
+				methods.push(CallVariantDef { name: fn_name, receiver_kind, caller_kind, args });
 			}
 		}
 
 		// Return all callable functions for this pallet.
-		Ok(Self { pallet_struct, methods })
+		Ok(Self { pallet_struct, where_clause, methods })
 	}
 }
 
-/// Check caller arg is exactly: `caller: T::AccountId`.
+/// Check runtime arg is exactly: `runtime: &mut T`.
 ///
 /// This is kept strict to keep the code simple.
-pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
+pub fn check_runtime_arg(arg: &syn::PatType) -> syn::Result<()> {
+	pub struct CheckDispatchableRuntimeArg;
+	impl syn::parse::Parse for CheckDispatchableRuntimeArg {
+		fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+			input.parse::<syn::Token![&]>()?;
+			input.parse::<syn::Token![mut]>()?;
+			input.parse::<keyword::T>()?;
+			Ok(Self)
+		}
+	}
+
+	// This checks the arg name is `runtime` or `_runtime`.
+	if let syn::Pat::Ident(ident) = &*arg.pat {
+		if &ident.ident != "runtime" && &ident.ident != "_runtime" {
+			let msg = "Invalid name for first parameter: expected a variant of self or \
+				`runtime: &mut T`";
+			return Err(syn::Error::new(ident.span(), msg))
+		}
+	}
+
+	// This checks the type is `&mut T` with `CheckDispatchableRuntimeArg`.
+	let ty = &arg.ty;
+	syn::parse2::<CheckDispatchableRuntimeArg>(ty.to_token_stream()).map_err(|e| {
+		let msg = "Invalid type for first parameter: expected `runtime: &mut T`";
+		let mut err = syn::Error::new(ty.span(), msg);
+		err.combine(e);
+		err
+	})?;
+
+	Ok(())
+}
+
+/// Check caller arg is exactly: `caller: T::AccountId` or `origin: T::RuntimeOrigin`.
+///
+/// This is kept strict to keep the code simple.
+pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<CallerKind> {
 	pub struct CheckDispatchableFirstArg;
 	impl syn::parse::Parse for CheckDispatchableFirstArg {
 		fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
@@ -119,23 +191,53 @@ pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
 		}
 	}
 
-	// This checks the arg name is `caller` or `_caller`.
-	if let syn::Pat::Ident(ident) = &*arg.pat {
-		// We also support the name as `_caller` for when the variable is unused.
-		if &ident.ident != "caller" && &ident.ident != "_caller" {
-			let msg = "Invalid name for second parameter: expected `caller: T::AccountId`";
-			return Err(syn::Error::new(ident.span(), msg))
+	pub struct CheckDispatchableOriginArg;
+	impl syn::parse::Parse for CheckDispatchableOriginArg {
+		fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+			input.parse::<keyword::T>()?;
+			input.parse::<syn::Token![::]>()?;
+			input.parse::<keyword::RuntimeOrigin>()?;
+			Ok(Self)
 		}
 	}
 
-	// This checks the type is `T::AccountId` with `CheckDispatchableFirstArg`
+	// This checks the arg name is `caller`/`_caller` or `origin`/`_origin`.
+	let caller_kind = if let syn::Pat::Ident(ident) = &*arg.pat {
+		// We also support the names with a leading `_` for when the variable is unused.
+		if &ident.ident == "caller" || &ident.ident == "_caller" {
+			CallerKind::Caller
+		} else if &ident.ident == "origin" || &ident.ident == "_origin" {
+			CallerKind::Origin
+		} else {
+			let msg = "Invalid name for second parameter: expected `caller: T::AccountId` or \
+				`origin: T::RuntimeOrigin`";
+			return Err(syn::Error::new(ident.span(), msg))
+		}
+	} else {
+		let msg = "Invalid pallet::call, argument must be ident";
+		return Err(syn::Error::new(arg.pat.span(), msg))
+	};
+
+	// This checks the type matches the name, with `CheckDispatchableFirstArg` or
+	// `CheckDispatchableOriginArg`.
 	let ty = &arg.ty;
-	syn::parse2::<CheckDispatchableFirstArg>(ty.to_token_stream()).map_err(|e| {
-		let msg = "Invalid type for second parameter: expected `caller: T::AccountId`";
+	let checked = match caller_kind {
+		CallerKind::Caller =>
+			syn::parse2::<CheckDispatchableFirstArg>(ty.to_token_stream()).map(|_| ()),
+		CallerKind::Origin =>
+			syn::parse2::<CheckDispatchableOriginArg>(ty.to_token_stream()).map(|_| ()),
+	};
+	checked.map_err(|e| {
+		let msg = match caller_kind {
+			CallerKind::Caller =>
+				"Invalid type for second parameter: expected `caller: T::AccountId`",
+			CallerKind::Origin =>
+				"Invalid type for second parameter: expected `origin: T::RuntimeOrigin`",
+		};
 		let mut err = syn::Error::new(ty.span(), msg);
 		err.combine(e);
 		err
 	})?;
 
-	Ok(())
+	Ok(caller_kind)
 }
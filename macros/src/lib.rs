@@ -1,6 +1,28 @@
 mod call;
 mod runtime;
 
+/// Expand the callable functions of a pallet.
+///
+/// This is placed on an `impl` block of the pallet struct, where every function is callable:
+/// - The first parameter is either a variant of `self`, for functions which only need their own
+///   pallet, or `runtime: &mut T`, for functions which need the whole runtime, e.g. to dispatch
+///   other calls. The bounds in the `where` clause of the `impl` block are kept for the latter.
+/// - The second parameter is either `caller: T::AccountId`, for functions which only accept signed
+///   calls, or `origin: T::RuntimeOrigin`, for functions which check the origin themselves.
+///
+/// Functions taking the `runtime` reach other pallets through `AsRef`/`AsMut` bounds on `T` in the
+/// `where` clause, e.g. `T: AsMut<T::Currency> + AsRef<system::Pallet<T>>`, naming them either
+/// concretely or through an associated type of the pallet's `Config`. Each pallet is borrowed in
+/// turn, so the runtime is never borrowed mutably twice.
+///
+/// Functions taking a nested call take it as a `Box<T::RuntimeCall>`, since the `Call` of their own
+/// pallet is part of the `RuntimeCall`. They are handed the box as it is, so their `impl` blocks
+/// allow `clippy::boxed_local`.
+///
+/// This generates an `enum Call` with a variant for each function, and implements the trait
+/// `support::Dispatchable` on it to dispatch calls to the appropriate function,
+/// `support::GetCallName` to name them after their function, and `support::Encode` to encode them
+/// as the index of their function followed by their arguments.
 #[proc_macro_attribute]
 pub fn call(
 	attr: proc_macro::TokenStream,
@@ -16,15 +38,44 @@ pub fn call(
 ///   included in the runtime.
 /// - `fn execute_block()` - which handles basic logic for executing a block of extrinsics. It does
 ///   basic actions like incrementing the block number and checking the block to be executed has a
-///   valid block number.
+///   valid block number. Every pallet implements `support::ClearEvents`, so the events of the
+///   previous block are cleared first. The pallet fields marked with `#[hooks]` implement
+///   `support::Hooks`, which is called before and after the extrinsics of every block. An invalid
+///   block is rolled back as a whole with `support::with_transaction`, so `Runtime` must be
+///   `Clone`.
+/// - `fn create_inherents()`, `fn check_inherents()` and `fn import_block()` - for the inherents of
+///   the pallet fields marked with `#[inherent]`, which implement `support::ProvideInherent`.
+///   Inherents are unsigned extrinsics the author of a block puts at its start. `execute_block`
+///   rejects blocks with inherents anywhere else, or without the inherents which are required, and
+///   `import_block` also checks the inherents of a block made by someone else against our own data.
+/// - `fn validate_unsigned()` - which asks the pallet of an unsigned extrinsic whether to dispatch
+///   it. Only the pallet fields marked with `#[unsigned]`, which implement
+///   `support::ValidateUnsigned`, approve any.
+///
+/// It also implements `AsRef` and `AsMut` on `Runtime` for each of the pallets, which is how the
+/// calls taking the `runtime` reach the pallets they need.
+///
+/// The `Runtime` derives `Clone` and `Debug`, so every pallet does too. Pallets spell the types of
+/// their fields out, e.g. `Scheduled<T::RuntimeCall, T::AccountId, T::BlockNumber>` rather than
+/// `ScheduledOf<T>`, so that those derives see the associated types they need to bound. Clippy may
+/// find these types complex, which is allowed on the fields.
 ///
 /// This also generates code needed for dispatching calls to the pallets:
-/// - Note: For simplicity, we assume that the system pallet is not callable.
+/// - `enum RuntimeOrigin` - the origin calls are dispatched with. It wraps `system::RawOrigin`
+///   (root, signed or none), plus the custom `Origin<T>` type of every pallet field marked with
+///   `#[origin]`.
 /// - `enum RuntimeCall` - an "outer"-enum representing the accumulation of all possible calls to
-///   all pallets. The system pallet is not included.
-/// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. Basic
-///   logic like incrementing the nonce of the user is included in the generated code. The system
-///   pallet is not included.
+///   all pallets, including the system pallet. It can be built `From` any pallet's `Call`, and
+///   implements `support::Dispatchable` so pallets can dispatch nested calls, and
+///   `support::GetCallMetadata` to name calls after their pallet and function, and
+///   `support::Encode` to encode them after the index of their pallet. The runtime names it as
+///   its `system::Config::RuntimeCall`, and this implements `system::RuntimeCallConfig` so pallets
+///   can dispatch it.
+/// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet, once the
+///   `system::Config::BaseCallFilter` of the runtime allows them, unless their origin is root. Basic
+///   logic like incrementing the nonce of the user is included in the generated code, and signed
+///   extrinsics are dispatched with a signed origin, while inherents and unsigned extrinsics are
+///   dispatched with the none origin.
 #[proc_macro_attribute]
 pub fn runtime(
 	attr: proc_macro::TokenStream,
@@ -3,12 +3,33 @@ use quote::quote;
 
 /// See the `fn runtime` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
-	let RuntimeDef { runtime_struct, pallets } = def;
+	let RuntimeDef { runtime_struct, pallets, origins, hooks, inherents, unsigned } = def;
 
-	// This is a vector of all the pallet names, not including system.
+	// This is a vector of all the pallet names, including system.
 	let pallet_names = pallets.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
-	// This is a vector of all the pallet types, not including system.
+	// This is a vector of all the pallet types, including system.
 	let pallet_types = pallets.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+	// This is a vector of the index of each pallet, which calls are encoded with.
+	let pallet_indices = (0..pallets.len()).map(|index| index as u8).collect::<Vec<_>>();
+	// This is a vector of the types of the pallets with hooks, in the order they are declared.
+	let hook_types = hooks.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+	// These are the names and types of the pallets with inherents.
+	let inherent_names = inherents.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+	let inherent_types = inherents.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+	// These are the names and types of the pallets which accept unsigned calls.
+	let unsigned_names = unsigned.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+	let unsigned_types = unsigned.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+
+	// The types of the extrinsics and blocks of the runtime.
+	let extrinsic_type = quote! {
+		crate::support::Extrinsic<<#runtime_struct as crate::system::Config>::AccountId, RuntimeCall>
+	};
+	let block_type = quote! {
+		crate::support::Block<
+			crate::support::Header<<#runtime_struct as crate::system::Config>::BlockNumber>,
+			#extrinsic_type,
+		>
+	};
 
 	// This quote block implements functions on the `Runtime` struct.
 	let runtime_impl = quote! {
@@ -16,64 +37,348 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 			// Create a new instance of the main Runtime, by creating a new instance of each pallet.
 			fn new() -> Self {
 				Self {
-					// Since system is not included in the list of pallets, we manually add it here.
-					system: <system::Pallet::<Self>>::new(),
 					#(
 						#pallet_names: <#pallet_types>::new()
 					),*
 				}
 			}
 
-			// Execute a block of extrinsics. Increments the block number.
-			fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
+			// Whether `call` is an inherent of one of the pallets marked with `#[inherent]`.
+			fn is_inherent(call: &RuntimeCall) -> bool {
+				#[allow(unreachable_patterns, clippy::match_single_binding)]
+				match call {
+					#(
+						RuntimeCall::#inherent_names(call) => {
+							<#inherent_types as crate::support::ProvideInherent>::is_inherent(call)
+						}
+					)*
+					_ => false,
+				}
+			}
+
+			// The inherents the author of a block puts at its start, made from its `data`.
+			fn create_inherents(data: &crate::support::InherentData) -> Vec<#extrinsic_type> {
+				#[allow(unused_mut)]
+				let mut extrinsics = Vec::new();
+				#(
+					let inherent = <#inherent_types as crate::support::ProvideInherent>::create_inherent(data);
+					if let Some(call) = inherent {
+						let call = RuntimeCall::#inherent_names(call);
+						extrinsics.push(crate::support::Extrinsic::Inherent { call });
+					}
+				)*
+				extrinsics
+			}
+
+			// Check the inherents of a block made by someone else against our own `data`.
+			fn check_inherents(
+				block: &#block_type,
+				#[allow(unused_variables)]
+				data: &crate::support::InherentData,
+			) -> crate::support::DispatchResult {
+				for extrinsic in &block.extrinsics {
+					if let crate::support::Extrinsic::Inherent { call } = extrinsic {
+						#[allow(unreachable_patterns, clippy::match_single_binding)]
+						match call {
+							#(
+								RuntimeCall::#inherent_names(call) => {
+									<#inherent_types as crate::support::ProvideInherent>::check_inherent(call, data)?
+								}
+							)*
+							_ => {}
+						}
+					}
+				}
+				Ok(())
+			}
+
+			// Check that the pallet of the unsigned `call` approves it. Only the pallets marked with
+			// `#[unsigned]` approve any.
+			fn validate_unsigned(&self, call: &RuntimeCall) -> crate::support::DispatchResult {
+				#[allow(unreachable_patterns, clippy::match_single_binding)]
+				match call {
+					#(
+						RuntimeCall::#unsigned_names(call) => {
+							<#unsigned_types as crate::support::ValidateUnsigned<#runtime_struct>>::validate_unsigned(self, call)
+						}
+					)*
+					_ => Err("unsigned calls to this pallet are not allowed"),
+				}
+			}
+
+			// Import a block made by someone else: check its inherents against our own `data`, then
+			// execute it.
+			fn import_block(
+				&mut self,
+				block: #block_type,
+				data: &crate::support::InherentData,
+			) -> crate::support::DispatchResult {
+				Self::check_inherents(&block, data)?;
+				self.execute_block(block)
+			}
+
+			// Check that the inherents of a block come before every other extrinsic, are really
+			// inherents, and include every inherent which is required.
+			fn check_extrinsics(extrinsics: &[#extrinsic_type]) -> crate::support::DispatchResult {
+				let count = extrinsics
+					.iter()
+					.take_while(|extrinsic| matches!(extrinsic, crate::support::Extrinsic::Inherent { .. }))
+					.count();
+				let (inherents, others) = extrinsics.split_at(count);
+				if others.iter().any(|extrinsic| matches!(extrinsic, crate::support::Extrinsic::Inherent { .. })) {
+					return Err("inherents must come before the other extrinsics")
+				}
+				for extrinsic in inherents {
+					if let crate::support::Extrinsic::Inherent { call } = extrinsic {
+						if !Self::is_inherent(call) {
+							return Err("extrinsic is not an inherent")
+						}
+					}
+				}
+				#(
+					let required = <#inherent_types as crate::support::ProvideInherent>::is_inherent_required();
+					let included = inherents.iter().any(|extrinsic| matches!(
+						extrinsic,
+						crate::support::Extrinsic::Inherent { call: RuntimeCall::#inherent_names(_) }
+					));
+					if required && !included {
+						return Err("block is missing a required inherent")
+					}
+				)*
+				Ok(())
+			}
+
+			// Execute a block of extrinsics. Increments the block number, clears the events of the
+			// previous block, and calls the hooks of the pallets marked with `#[hooks]` before and
+			// after the extrinsics.
+			//
+			// Inherents are dispatched with the none origin, and the block is invalid if they fail.
+			// Signed extrinsics are dispatched with a signed origin, and unsigned ones with the none
+			// origin if their pallet approves them. Either only reports its errors.
+			//
+			// An invalid block is rolled back as a whole, so the runtime is left as it was before.
+			fn execute_block(&mut self, block: #block_type) -> crate::support::DispatchResult {
+				Self::check_extrinsics(&block.extrinsics)?;
+				crate::support::with_transaction(self, |runtime| runtime.apply_block(block))
+			}
+
+			// Apply a block whose extrinsics were checked, see `execute_block`.
+			fn apply_block(&mut self, block: #block_type) -> crate::support::DispatchResult {
 				self.system.inc_block_number();
 				if block.header.block_number != self.system.block_number() {
 					return Err(&"block number does not match what is expected")
 				}
-				for (i, support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
-					self.system.inc_nonce(&caller);
-					let _res = self.dispatch(caller, call).map_err(|e| {
+				#(
+					crate::support::ClearEvents::clear_events(&mut self.#pallet_names);
+				)*
+				#(
+					<#hook_types as crate::support::Hooks<#runtime_struct>>::on_initialize(self);
+				)*
+				for (i, extrinsic) in block.extrinsics.into_iter().enumerate() {
+					let result = match extrinsic {
+						crate::support::Extrinsic::Signed { caller, call } => {
+							self.system.inc_nonce(&caller);
+							crate::support::Dispatch::dispatch(self, RuntimeOrigin::signed(caller), call)
+						}
+						crate::support::Extrinsic::Inherent { call } => {
+							crate::support::Dispatch::dispatch(self, RuntimeOrigin::none(), call)?;
+							Ok(())
+						}
+						crate::support::Extrinsic::Unsigned { call } => {
+							self.validate_unsigned(&call).and_then(|()| {
+								crate::support::Dispatch::dispatch(self, RuntimeOrigin::none(), call)
+							})
+						}
+					};
+					let _res = result.map_err(|e| {
 						eprintln!(
 							"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
 							block.header.block_number, i, e
 						)
 					});
+					self.system.note_applied_extrinsic();
 				}
+				#(
+					<#hook_types as crate::support::Hooks<#runtime_struct>>::on_finalize(self);
+				)*
 				Ok(())
 			}
 		}
 	};
 
+	// This quote block implements the `RuntimeOrigin` enum, which wraps the origin of the system
+	// pallet along with the custom origins of the pallets marked with `#[origin]`.
+	let origin_impl = quote! {
+		// The origin of a call, used to determine what the call is allowed to do.
+		//
+		// The variant names are the pallet names, so they will be `snake_case`.
+		#[derive(Clone, Debug)]
+		#[allow(non_camel_case_types)]
+		pub enum RuntimeOrigin {
+			system(crate::system::RawOrigin<<#runtime_struct as crate::system::Config>::AccountId>),
+			#( #origins(#origins::Origin<#runtime_struct>) ),*
+		}
+
+		impl RuntimeOrigin {
+			// The root origin, with maximum privileges.
+			pub fn root() -> Self {
+				crate::system::RawOrigin::Root.into()
+			}
+
+			// The origin of a call signed by `who`.
+			pub fn signed(who: <#runtime_struct as crate::system::Config>::AccountId) -> Self {
+				crate::system::RawOrigin::Signed(who).into()
+			}
+
+			// The origin of a call which was not signed.
+			pub fn none() -> Self {
+				crate::system::RawOrigin::None.into()
+			}
+		}
+
+		impl From<crate::system::RawOrigin<<#runtime_struct as crate::system::Config>::AccountId>> for RuntimeOrigin {
+			fn from(origin: crate::system::RawOrigin<<#runtime_struct as crate::system::Config>::AccountId>) -> Self {
+				RuntimeOrigin::system(origin)
+			}
+		}
+
+		// This is what lets `system::ensure_signed` and friends look into a `RuntimeOrigin`.
+		impl From<RuntimeOrigin> for Result<crate::system::RawOrigin<<#runtime_struct as crate::system::Config>::AccountId>, RuntimeOrigin> {
+			fn from(origin: RuntimeOrigin) -> Self {
+				match origin {
+					RuntimeOrigin::system(origin) => Ok(origin),
+					#[allow(unreachable_patterns)]
+					origin => Err(origin),
+				}
+			}
+		}
+
+		#(
+			impl From<#origins::Origin<#runtime_struct>> for RuntimeOrigin {
+				fn from(origin: #origins::Origin<#runtime_struct>) -> Self {
+					RuntimeOrigin::#origins(origin)
+				}
+			}
+
+			impl From<RuntimeOrigin> for Result<#origins::Origin<#runtime_struct>, RuntimeOrigin> {
+				fn from(origin: RuntimeOrigin) -> Self {
+					match origin {
+						RuntimeOrigin::#origins(origin) => Ok(origin),
+						origin => Err(origin),
+					}
+				}
+			}
+		)*
+	};
+
 	// This quote block implements the `RuntimeCall` enum and implements the `Dispatch` trait.
 	let dispatch_impl = quote! {
 		// These are all the calls which are exposed to the world.
 		// Note that it is just an accumulation of the calls exposed by each pallet.
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
+		#[derive(Clone, Debug)]
 		#[allow(non_camel_case_types)]
 		pub enum RuntimeCall {
 			#( #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
 		}
 
+		#(
+			impl From<#pallet_names::Call<#runtime_struct>> for RuntimeCall {
+				fn from(call: #pallet_names::Call<#runtime_struct>) -> Self {
+					RuntimeCall::#pallet_names(call)
+				}
+			}
+		)*
+
+		// The name of each call is the name of its pallet, and its name in that pallet.
+		impl crate::support::GetCallMetadata for RuntimeCall {
+			fn pallet_name(&self) -> &'static str {
+				match self {
+					#( RuntimeCall::#pallet_names(_) => stringify!(#pallet_names), )*
+				}
+			}
+
+			fn call_name(&self) -> &'static str {
+				match self {
+					#( RuntimeCall::#pallet_names(call) => crate::support::GetCallName::call_name(call), )*
+				}
+			}
+
+			fn pallet_names() -> &'static [&'static str] {
+				&[ #( stringify!(#pallet_names) ),* ]
+			}
+
+			fn call_names(pallet_name: &str) -> &'static [&'static str] {
+				match pallet_name {
+					#(
+						stringify!(#pallet_names) => {
+							<#pallet_names::Call<#runtime_struct> as crate::support::GetCallName>::call_names()
+						}
+					)*
+					_ => &[],
+				}
+			}
+		}
+
+		// A call is encoded as the index of its pallet, followed by the encoding of the call within
+		// that pallet.
+		impl crate::support::Encode for RuntimeCall {
+			fn encode_to(&self, buffer: &mut Vec<u8>) {
+				match self {
+					#(
+						RuntimeCall::#pallet_names(call) => {
+							buffer.push(#pallet_indices);
+							crate::support::Encode::encode_to(call, buffer);
+						}
+					)*
+				}
+			}
+		}
+
+		// This lets pallets dispatch a nested `RuntimeCall` they were given, without knowing about
+		// the concrete runtime.
+		impl crate::support::Dispatchable<#runtime_struct> for RuntimeCall {
+			type Origin = RuntimeOrigin;
+
+			fn dispatch(
+				self,
+				runtime: &mut #runtime_struct,
+				origin: Self::Origin,
+			) -> crate::support::DispatchResult {
+				crate::support::Dispatch::dispatch(runtime, origin, self)
+			}
+		}
+
+		impl crate::system::RuntimeCallConfig for #runtime_struct {}
+
 		impl crate::support::Dispatch for #runtime_struct {
-			type Caller = <Runtime as system::Config>::AccountId;
+			type Origin = RuntimeOrigin;
 			type Call = RuntimeCall;
-			// Dispatch a call on behalf of a caller. Increments the caller's nonce.
+			// Dispatch a call with some origin.
 			//
 			// Dispatch allows us to identify which underlying pallet call we want to execute.
-			// Note that we extract the `caller` from the extrinsic, and use that information
-			// to determine who we are executing the call on behalf of.
+			// Note that the `origin` is what we use to determine who we are executing the call
+			// on behalf of, if anyone.
 			fn dispatch(
 				&mut self,
-				caller: Self::Caller,
+				origin: Self::Origin,
 				runtime_call: Self::Call,
 			) -> crate::support::DispatchResult {
+				// Calls the runtime does not allow right now are rejected before they reach their
+				// pallet, whether they come from an extrinsic or are nested in another call. Root
+				// is not filtered, so it can still make calls while they are paused.
+				let filter = <<Self as crate::system::Config>::BaseCallFilter as crate::support::Contains<Self, RuntimeCall>>::contains;
+				let is_root = matches!(origin, RuntimeOrigin::system(crate::system::RawOrigin::Root));
+				if !is_root && !filter(self, &runtime_call) {
+					return Err("call is filtered");
+				}
 				// This match statement will allow us to correctly route `RuntimeCall`s
 				// to the appropriate pallet level call.
 				match runtime_call {
 					#(
 						RuntimeCall::#pallet_names(call) => {
-							self.#pallet_names.dispatch(caller, call)?;
+							crate::support::Dispatchable::dispatch(call, self, origin)?;
 						}
 					),*
 				}
@@ -82,9 +387,29 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		}
 	};
 
+	// This quote block gives access to each pallet from the runtime, which is how calls taking the
+	// `runtime` reach the pallets they need.
+	let access_impl = quote! {
+		#(
+			impl core::convert::AsRef<#pallet_types> for #runtime_struct {
+				fn as_ref(&self) -> &#pallet_types {
+					&self.#pallet_names
+				}
+			}
+
+			impl core::convert::AsMut<#pallet_types> for #runtime_struct {
+				fn as_mut(&mut self) -> &mut #pallet_types {
+					&mut self.#pallet_names
+				}
+			}
+		)*
+	};
+
 	// We combine and return all the generated code.
 	quote! {
+		#origin_impl
 		#dispatch_impl
+		#access_impl
 		#runtime_impl
 	}
 	.into()
@@ -6,14 +6,18 @@ pub fn runtime(
 	_attr: proc_macro::TokenStream,
 	item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-	// The final expanded code will be placed here.
-	// Since our macro only adds new code, our final product will contain all of our old code too,
-	// hence we clone `item`.
-	let mut finished = item.clone();
 	let item_mod = syn::parse_macro_input!(item as syn::Item);
 
+	// The final expanded code will be placed here.
+	// Since our macro mostly adds new code, our final product will contain all of our old code too,
+	// except for the `#[origin]`, `#[hooks]`, `#[inherent]` and `#[unsigned]` markers which only
+	// make sense to this macro.
+	let mut stripped = item_mod.clone();
+	parse::strip_marker_attrs(&mut stripped);
+	let mut finished: proc_macro::TokenStream = quote::quote!(#stripped).into();
+
 	// First we parse the `Runtime` struct...
-	let generated: proc_macro::TokenStream = match parse::RuntimeDef::try_from(item_mod.clone()) {
+	let generated: proc_macro::TokenStream = match parse::RuntimeDef::try_from(item_mod) {
 		// ..then we generate our new code.
 		Ok(def) => expand::expand_runtime(def).into(),
 		Err(e) => e.to_compile_error().into(),
@@ -5,9 +5,21 @@ use syn::spanned::Spanned;
 pub struct RuntimeDef {
 	/// This is the name of the struct used by the user. We mostly assume it is `Runtime`.
 	pub runtime_struct: syn::Ident,
-	/// This is the list of pallets included in the `Runtime` struct. We omit `system` from this
-	/// list, but during parsing we check that system exists.
+	/// This is the list of pallets included in the `Runtime` struct. During parsing we check that
+	/// `system` exists and is the first one.
 	pub pallets: Vec<(syn::Ident, syn::Type)>,
+	/// This is the list of pallets marked with `#[origin]`, which define a custom `Origin<T>` type
+	/// to be included in the `RuntimeOrigin`.
+	pub origins: Vec<syn::Ident>,
+	/// This is the list of pallets marked with `#[hooks]`, which implement `support::Hooks` and are
+	/// called at the start and end of every block, in the order they are declared.
+	pub hooks: Vec<(syn::Ident, syn::Type)>,
+	/// This is the list of pallets marked with `#[inherent]`, which implement
+	/// `support::ProvideInherent` for the inherents at the start of every block.
+	pub inherents: Vec<(syn::Ident, syn::Type)>,
+	/// This is the list of pallets marked with `#[unsigned]`, which implement
+	/// `support::ValidateUnsigned` to accept some of their calls unsigned.
+	pub unsigned: Vec<(syn::Ident, syn::Type)>,
 }
 
 impl RuntimeDef {
@@ -26,21 +38,73 @@ impl RuntimeDef {
 
 		// Here is where we will store a list of all the pallets.
 		let mut pallets = vec![];
-		// We skip `system`, which we ensure is the first field in `check_system`.
-		for field in item_struct.fields.into_iter().skip(1) {
+		// And here the pallets which have a custom origin, hooks, inherents, or unsigned calls.
+		let mut origins = vec![];
+		let mut hooks = vec![];
+		let mut inherents = vec![];
+		let mut unsigned = vec![];
+		// `system` is included too, and we ensure it is the first field in `check_system`.
+		for field in item_struct.fields.into_iter() {
 			if let Some(ident) = field.ident {
+				if field.attrs.iter().any(is_origin_attr) {
+					origins.push(ident.clone());
+				}
+				if field.attrs.iter().any(is_hooks_attr) {
+					hooks.push((ident.clone(), field.ty.clone()));
+				}
+				if field.attrs.iter().any(is_inherent_attr) {
+					inherents.push((ident.clone(), field.ty.clone()));
+				}
+				if field.attrs.iter().any(is_unsigned_attr) {
+					unsigned.push((ident.clone(), field.ty.clone()));
+				}
 				pallets.push((ident, field.ty))
 			}
 		}
 
-		Ok(Self { runtime_struct, pallets })
+		Ok(Self { runtime_struct, pallets, origins, hooks, inherents, unsigned })
+	}
+}
+
+/// Whether `attr` is the `#[origin]` marker we accept on pallet fields.
+fn is_origin_attr(attr: &syn::Attribute) -> bool {
+	attr.path().is_ident("origin")
+}
+
+/// Whether `attr` is the `#[hooks]` marker we accept on pallet fields.
+fn is_hooks_attr(attr: &syn::Attribute) -> bool {
+	attr.path().is_ident("hooks")
+}
+
+/// Whether `attr` is the `#[inherent]` marker we accept on pallet fields.
+fn is_inherent_attr(attr: &syn::Attribute) -> bool {
+	attr.path().is_ident("inherent")
+}
+
+/// Whether `attr` is the `#[unsigned]` marker we accept on pallet fields.
+fn is_unsigned_attr(attr: &syn::Attribute) -> bool {
+	attr.path().is_ident("unsigned")
+}
+
+/// Remove the `#[origin]`, `#[hooks]`, `#[inherent]` and `#[unsigned]` markers from the fields of
+/// the `Runtime` struct, since they are only meaningful to this macro and would not compile
+/// otherwise.
+pub fn strip_marker_attrs(item: &mut syn::Item) {
+	if let syn::Item::Struct(item_struct) = item {
+		for field in item_struct.fields.iter_mut() {
+			field.attrs.retain(|attr| {
+				!is_origin_attr(attr) &&
+					!is_hooks_attr(attr) && !is_inherent_attr(attr) &&
+					!is_unsigned_attr(attr)
+			});
+		}
 	}
 }
 
 /// This function checks that the `system` pallet is the first pallet included in the `Runtime`
 /// struct. We make many assumptions about the `system` pallet in order to keep these macros simple.
-/// For example, we assume that the system pallet has no callable functions, and that it contains
-/// specific functions like incrementing the block number and a user's nonce.
+/// For example, we assume that the system pallet contains specific functions like incrementing the
+/// block number and a user's nonce.
 ///
 /// You can consider these macros to be tightly coupled to the logic of the `system` pallet.
 fn check_system(item_struct: &syn::ItemStruct) -> syn::Result<()> {
@@ -0,0 +1,396 @@
+use core::fmt::Debug;
+use std::collections::BTreeMap;
+
+use num::traits::{CheckedAdd, CheckedSub, Zero};
+
+use crate::support::{ClearEvents, DispatchResult};
+
+pub trait Config: crate::system::Config {
+	/// The identifier of an asset.
+	type AssetId: Ord + Clone + Debug;
+	/// The balance of an account in some asset.
+	type AssetBalance: CheckedAdd + CheckedSub + Zero + Copy + PartialOrd + Debug;
+}
+
+/// What we know about an asset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetDetails<AccountId, Balance> {
+	/// The account which created the asset, and can set its metadata or destroy it.
+	pub owner: AccountId,
+	/// The account which can mint, burn, freeze and thaw the asset.
+	pub admin: AccountId,
+	/// The minimum balance an account needs to hold the asset. Accounts left with less are
+	/// reaped, and whatever they had left is lost.
+	pub min_balance: Balance,
+	/// The sum of all the balances in the asset.
+	pub supply: Balance,
+}
+
+/// The balance of an account in some asset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetAccount<Balance> {
+	pub balance: Balance,
+	/// Whether the account is stopped from transferring the asset.
+	pub is_frozen: bool,
+}
+
+/// The descriptive metadata of an asset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetMetadata {
+	pub name: Vec<u8>,
+	pub symbol: Vec<u8>,
+	pub decimals: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	assets: BTreeMap<T::AssetId, AssetDetails<T::AccountId, T::AssetBalance>>,
+	/// The balance of each account in each asset, keyed by `(asset_id, account)`.
+	#[allow(clippy::type_complexity)]
+	accounts: BTreeMap<(T::AssetId, T::AccountId), AssetAccount<T::AssetBalance>>,
+	metadata: BTreeMap<T::AssetId, AssetMetadata>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { assets: BTreeMap::new(), accounts: BTreeMap::new(), metadata: BTreeMap::new() }
+	}
+
+	/// What we know about the asset `id`, if it exists.
+	pub fn asset(&self, id: &T::AssetId) -> Option<&AssetDetails<T::AccountId, T::AssetBalance>> {
+		self.assets.get(id)
+	}
+
+	/// The balance of `who` in the asset `id`.
+	pub fn balance(&self, id: &T::AssetId, who: &T::AccountId) -> T::AssetBalance {
+		self.accounts
+			.get(&(id.clone(), who.clone()))
+			.map(|account| account.balance)
+			.unwrap_or(T::AssetBalance::zero())
+	}
+
+	/// The metadata of the asset `id`, if any was set.
+	pub fn metadata(&self, id: &T::AssetId) -> Option<&AssetMetadata> {
+		self.metadata.get(id)
+	}
+
+	fn details(
+		&self,
+		id: &T::AssetId,
+	) -> Result<&AssetDetails<T::AccountId, T::AssetBalance>, &'static str> {
+		self.assets.get(id).ok_or("unknown asset")
+	}
+
+	fn ensure_admin(&self, id: &T::AssetId, who: &T::AccountId) -> DispatchResult {
+		if self.details(id)?.admin != *who {
+			return Err("sender is not the asset admin");
+		}
+		Ok(())
+	}
+
+	fn ensure_owner(&self, id: &T::AssetId, who: &T::AccountId) -> DispatchResult {
+		if self.details(id)?.owner != *who {
+			return Err("sender is not the asset owner");
+		}
+		Ok(())
+	}
+
+	// Set the balance of `who` in the asset `id`, reaping the account if it is below the minimum
+	// balance. The supply follows.
+	fn set_balance(
+		&mut self,
+		id: &T::AssetId,
+		who: &T::AccountId,
+		amount: T::AssetBalance,
+	) -> DispatchResult {
+		let old = self.balance(id, who);
+		let details = self.assets.get_mut(id).ok_or("unknown asset")?;
+		let new = if amount < details.min_balance { T::AssetBalance::zero() } else { amount };
+		details.supply = details
+			.supply
+			.checked_sub(&old)
+			.and_then(|supply| supply.checked_add(&new))
+			.ok_or("Overflow")?;
+
+		let key = (id.clone(), who.clone());
+		if new.is_zero() {
+			self.accounts.remove(&key);
+		} else {
+			self.accounts
+				.entry(key)
+				.and_modify(|account| account.balance = new)
+				.or_insert(AssetAccount { balance: new, is_frozen: false });
+		}
+		Ok(())
+	}
+
+	fn set_frozen(
+		&mut self,
+		id: &T::AssetId,
+		who: &T::AccountId,
+		is_frozen: bool,
+	) -> DispatchResult {
+		let account = self
+			.accounts
+			.get_mut(&(id.clone(), who.clone()))
+			.ok_or("no balance in this asset")?;
+		account.is_frozen = is_frozen;
+		Ok(())
+	}
+}
+
+// The assets pallet deposits no events.
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {}
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Create the asset `asset_id`, owned by the caller and administered by `admin`.
+	pub fn create(
+		&mut self,
+		caller: T::AccountId,
+		asset_id: T::AssetId,
+		admin: T::AccountId,
+		min_balance: T::AssetBalance,
+	) -> DispatchResult {
+		if self.assets.contains_key(&asset_id) {
+			return Err("asset already exists");
+		}
+		if min_balance.is_zero() {
+			return Err("min balance should be non-zero");
+		}
+
+		let details =
+			AssetDetails { owner: caller, admin, min_balance, supply: T::AssetBalance::zero() };
+		self.assets.insert(asset_id, details);
+		Ok(())
+	}
+
+	/// Create `amount` of the asset `asset_id` in the account of `beneficiary`. Admin only.
+	pub fn mint(
+		&mut self,
+		caller: T::AccountId,
+		asset_id: T::AssetId,
+		beneficiary: T::AccountId,
+		amount: T::AssetBalance,
+	) -> DispatchResult {
+		self.ensure_admin(&asset_id, &caller)?;
+
+		let new_balance =
+			self.balance(&asset_id, &beneficiary).checked_add(&amount).ok_or("Overflow")?;
+		if new_balance < self.details(&asset_id)?.min_balance {
+			return Err("balance would be below the minimum balance");
+		}
+		self.set_balance(&asset_id, &beneficiary, new_balance)
+	}
+
+	/// Destroy `amount` of the asset `asset_id` from the account of `who`, reaping it if it is
+	/// left below the minimum balance. Admin only.
+	pub fn burn(
+		&mut self,
+		caller: T::AccountId,
+		asset_id: T::AssetId,
+		who: T::AccountId,
+		amount: T::AssetBalance,
+	) -> DispatchResult {
+		self.ensure_admin(&asset_id, &caller)?;
+
+		let new_balance =
+			self.balance(&asset_id, &who).checked_sub(&amount).ok_or("Not enough funds.")?;
+		self.set_balance(&asset_id, &who, new_balance)
+	}
+
+	/// Transfer `amount` of the asset `asset_id` to `to`, reaping the caller if it is left below
+	/// the minimum balance. Transferring to oneself changes nothing.
+	pub fn transfer(
+		&mut self,
+		caller: T::AccountId,
+		asset_id: T::AssetId,
+		to: T::AccountId,
+		amount: T::AssetBalance,
+	) -> DispatchResult {
+		let details = self.details(&asset_id)?;
+		let min_balance = details.min_balance;
+		let key = (asset_id.clone(), caller.clone());
+		if self.accounts.get(&key).is_some_and(|account| account.is_frozen) {
+			return Err("account is frozen");
+		}
+		if caller == to {
+			return Ok(());
+		}
+
+		let new_from_balance = self
+			.balance(&asset_id, &caller)
+			.checked_sub(&amount)
+			.ok_or("Not enough funds.")?;
+		let new_to_balance = self.balance(&asset_id, &to).checked_add(&amount).ok_or("Overflow")?;
+		if new_to_balance < min_balance {
+			return Err("balance would be below the minimum balance");
+		}
+
+		self.set_balance(&asset_id, &caller, new_from_balance)?;
+		self.set_balance(&asset_id, &to, new_to_balance)
+	}
+
+	/// Stop `who` from transferring the asset `asset_id`. Admin only.
+	pub fn freeze(
+		&mut self,
+		caller: T::AccountId,
+		asset_id: T::AssetId,
+		who: T::AccountId,
+	) -> DispatchResult {
+		self.ensure_admin(&asset_id, &caller)?;
+		self.set_frozen(&asset_id, &who, true)
+	}
+
+	/// Allow `who` to transfer the asset `asset_id` again. Admin only.
+	pub fn thaw(
+		&mut self,
+		caller: T::AccountId,
+		asset_id: T::AssetId,
+		who: T::AccountId,
+	) -> DispatchResult {
+		self.ensure_admin(&asset_id, &caller)?;
+		self.set_frozen(&asset_id, &who, false)
+	}
+
+	/// Set the metadata of the asset `asset_id`. Owner only.
+	pub fn set_metadata(
+		&mut self,
+		caller: T::AccountId,
+		asset_id: T::AssetId,
+		name: Vec<u8>,
+		symbol: Vec<u8>,
+		decimals: u8,
+	) -> DispatchResult {
+		self.ensure_owner(&asset_id, &caller)?;
+		self.metadata.insert(asset_id, AssetMetadata { name, symbol, decimals });
+		Ok(())
+	}
+
+	/// Destroy the asset `asset_id`, along with every balance in it and its metadata. Owner only.
+	pub fn destroy(&mut self, caller: T::AccountId, asset_id: T::AssetId) -> DispatchResult {
+		self.ensure_owner(&asset_id, &caller)?;
+		self.assets.remove(&asset_id);
+		self.accounts.retain(|(id, _), _| *id != asset_id);
+		self.metadata.remove(&asset_id);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = crate::system::RawOrigin<String>;
+		type RuntimeCall = ();
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl super::Config for TestConfig {
+		type AssetId = u32;
+		type AssetBalance = u128;
+	}
+
+	fn alice() -> String {
+		"alice".to_string()
+	}
+
+	fn bob() -> String {
+		"bob".to_string()
+	}
+
+	// Alice owns and administers asset 1, with a minimum balance of 10.
+	fn pallet() -> super::Pallet<TestConfig> {
+		let mut assets = super::Pallet::<TestConfig>::new();
+		assert_eq!(assets.create(alice(), 1, alice(), 10), Ok(()));
+		assets
+	}
+
+	#[test]
+	fn create_and_mint() {
+		let mut assets = pallet();
+		assert_eq!(assets.create(bob(), 1, bob(), 10), Err("asset already exists"));
+		assert_eq!(assets.create(bob(), 2, bob(), 0), Err("min balance should be non-zero"));
+
+		assert_eq!(assets.mint(bob(), 1, bob(), 100), Err("sender is not the asset admin"));
+		assert_eq!(assets.mint(alice(), 2, bob(), 100), Err("unknown asset"));
+		assert_eq!(
+			assets.mint(alice(), 1, bob(), 5),
+			Err("balance would be below the minimum balance")
+		);
+		assert_eq!(assets.mint(alice(), 1, bob(), 100), Ok(()));
+		assert_eq!(assets.mint(alice(), 1, bob(), u128::MAX), Err("Overflow"));
+		assert_eq!(assets.balance(&1, &bob()), 100);
+		assert_eq!(assets.asset(&1).map(|asset| asset.supply), Some(100));
+
+		assert_eq!(assets.burn(alice(), 1, bob(), 101), Err("Not enough funds."));
+		assert_eq!(assets.burn(alice(), 1, bob(), 40), Ok(()));
+		assert_eq!(assets.asset(&1).map(|asset| asset.supply), Some(60));
+	}
+
+	#[test]
+	fn transfer_assets() {
+		let mut assets = pallet();
+		assert_eq!(assets.mint(alice(), 1, alice(), 100), Ok(()));
+
+		assert_eq!(assets.transfer(bob(), 1, alice(), 10), Err("Not enough funds."));
+		assert_eq!(assets.transfer(alice(), 1, alice(), 50), Ok(()));
+		assert_eq!(assets.balance(&1, &alice()), 100);
+		assert_eq!(assets.asset(&1).map(|asset| asset.supply), Some(100));
+		assert_eq!(
+			assets.transfer(alice(), 1, bob(), 5),
+			Err("balance would be below the minimum balance")
+		);
+		assert_eq!(assets.transfer(alice(), 1, bob(), 95), Ok(()));
+		assert_eq!(assets.balance(&1, &bob()), 95);
+
+		// Alice is left with dust, which is lost.
+		assert_eq!(assets.balance(&1, &alice()), 0);
+		assert_eq!(assets.asset(&1).map(|asset| asset.supply), Some(95));
+	}
+
+	#[test]
+	fn freeze_and_thaw() {
+		let mut assets = pallet();
+		assert_eq!(assets.mint(alice(), 1, bob(), 100), Ok(()));
+
+		assert_eq!(assets.freeze(bob(), 1, bob()), Err("sender is not the asset admin"));
+		assert_eq!(
+			assets.freeze(alice(), 1, "charlie".to_string()),
+			Err("no balance in this asset")
+		);
+		assert_eq!(assets.freeze(alice(), 1, bob()), Ok(()));
+		assert_eq!(assets.transfer(bob(), 1, alice(), 50), Err("account is frozen"));
+
+		// The admin can still burn frozen funds.
+		assert_eq!(assets.burn(alice(), 1, bob(), 10), Ok(()));
+
+		assert_eq!(assets.thaw(alice(), 1, bob()), Ok(()));
+		assert_eq!(assets.transfer(bob(), 1, alice(), 50), Ok(()));
+	}
+
+	#[test]
+	fn metadata_and_destroy() {
+		let mut assets = pallet();
+		assert_eq!(assets.mint(alice(), 1, bob(), 100), Ok(()));
+
+		assert_eq!(
+			assets.set_metadata(bob(), 1, b"Token".to_vec(), b"TKN".to_vec(), 12),
+			Err("sender is not the asset owner")
+		);
+		assert_eq!(assets.set_metadata(alice(), 1, b"Token".to_vec(), b"TKN".to_vec(), 12), Ok(()));
+		assert_eq!(assets.metadata(&1).map(|metadata| metadata.decimals), Some(12));
+
+		assert_eq!(assets.destroy(bob(), 1), Err("sender is not the asset owner"));
+		assert_eq!(assets.destroy(alice(), 1), Ok(()));
+		assert_eq!(assets.asset(&1), None);
+		assert_eq!(assets.balance(&1, &bob()), 0);
+		assert_eq!(assets.metadata(&1), None);
+	}
+}
@@ -1,5 +1,14 @@
+use core::fmt::Debug;
 use std::collections::BTreeMap;
-use num::traits::{CheckedAdd, CheckedSub, Zero};
+use num::traits::{CheckedAdd, CheckedSub, Saturating, Zero};
+
+use crate::{
+	support::{
+		BalanceStatus, ClearEvents, Currency, DispatchResult, ExistenceRequirement, LockIdentifier,
+		LockableCurrency, ReservableCurrency, WithdrawReasons,
+	},
+	system::ensure_root,
+};
 
 
 /*TODO:
@@ -10,67 +19,591 @@ Types will now be defined in `main.rs`. See the TODOs there.
 */
 
 pub trait Config: crate::system::Config {
-    type Balance: CheckedAdd + CheckedSub + Zero + Copy; 
+    type Balance: CheckedAdd + CheckedSub + Saturating + Zero + Copy + PartialOrd + Debug; 
+    /// The minimum balance an account needs to exist. Accounts left with less than this are
+    /// reaped, and whatever they had left is lost.
+    const EXISTENTIAL_DEPOSIT: Self::Balance;
+}
+
+/// Some amount of the free balance of an account which can not be withdrawn for `reasons`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceLock<Balance> {
+	pub id: LockIdentifier,
+	pub amount: Balance,
+	pub reasons: WithdrawReasons,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
+	/// The free balance of each account, which it can spend.
 	balances: BTreeMap<T::AccountId, T::Balance>,
+	/// The reserved balance of each account, which is set aside e.g. for deposits.
+	reserved: BTreeMap<T::AccountId, T::Balance>,
+	/// The locks on the free balance of each account. They overlap, so only the largest one
+	/// matters for a given withdraw reason.
+	locks: BTreeMap<T::AccountId, Vec<BalanceLock<T::Balance>>>,
+	/// The sum of all the free and reserved balances above.
+	total_issuance: T::Balance,
 }
 
 impl<T: Config> Pallet<T> {
     //Initialize a new user balance
 	pub fn new() -> Self {
-		Self { balances: BTreeMap::new() }
+		Self {
+			balances: BTreeMap::new(),
+			reserved: BTreeMap::new(),
+			locks: BTreeMap::new(),
+			total_issuance: T::Balance::zero(),
+		}
 	}
 
-    //Set new user balance
+    //Set new user free balance, reaping the account if it is below the existential deposit. This
+    //creates or destroys tokens as needed, so the total issuance follows.
     pub fn set_balance(&mut self, who: &T::AccountId, amount: T::Balance) {
-        self.balances.insert(who.clone(), amount);
+        let old = self.balance(who);
+        self.write_free(who, amount);
+        self.total_issuance = self.total_issuance.saturating_sub(old).saturating_add(amount);
+        self.reap_if_dust(who);
     }
 
-    //Get User balance
+    //Get User free balance
     pub fn balance(&self, who: &T::AccountId) -> T::Balance{
         *self.balances.get(who).unwrap_or(&T::Balance::zero())
     }
+
+    /// The reserved balance of `who`.
+    pub fn reserved_balance(&self, who: &T::AccountId) -> T::Balance {
+        *self.reserved.get(who).unwrap_or(&T::Balance::zero())
+    }
+
+    /// The free and reserved balance of `who` together.
+    pub fn total_balance(&self, who: &T::AccountId) -> T::Balance {
+        self.balance(who).saturating_add(self.reserved_balance(who))
+    }
+
+    /// The total amount of tokens in existence.
+    pub fn total_issuance(&self) -> T::Balance {
+        self.total_issuance
+    }
+
+    /// Whether `who` has an account, i.e. holds at least the existential deposit in total.
+    pub fn account_exists(&self, who: &T::AccountId) -> bool {
+        let total = self.total_balance(who);
+        !total.is_zero() && total >= T::EXISTENTIAL_DEPOSIT
+    }
+
+    // Write the free balance of `who`, leaving the total issuance and reaping to the caller.
+    fn write_free(&mut self, who: &T::AccountId, amount: T::Balance) {
+        if amount.is_zero() {
+            self.balances.remove(who);
+        } else {
+            self.balances.insert(who.clone(), amount);
+        }
+    }
+
+    // Write the reserved balance of `who`, leaving the total issuance and reaping to the caller.
+    fn write_reserved(&mut self, who: &T::AccountId, amount: T::Balance) {
+        if amount.is_zero() {
+            self.reserved.remove(who);
+        } else {
+            self.reserved.insert(who.clone(), amount);
+        }
+    }
+
+    // Remove the account of `who` if it is below the existential deposit. What it had left is lost.
+    fn reap_if_dust(&mut self, who: &T::AccountId) {
+        if !self.account_exists(who) {
+            let dust = self.total_balance(who);
+            self.balances.remove(who);
+            self.reserved.remove(who);
+            self.locks.remove(who);
+            self.total_issuance = self.total_issuance.saturating_sub(dust);
+        }
+    }
+
+    /// The locks on the free balance of `who`.
+    pub fn locks(&self, who: &T::AccountId) -> &[BalanceLock<T::Balance>] {
+        self.locks.get(who).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Lock `amount` of the free balance of `who` against `reasons` under the name `id`, replacing
+    /// any lock with the same name. Locking nothing removes the lock.
+    pub fn set_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        if amount.is_zero() {
+            return self.remove_lock(id, who);
+        }
+        let locks = self.locks.entry(who.clone()).or_default();
+        let lock = BalanceLock { id, amount, reasons };
+        match locks.iter_mut().find(|lock| lock.id == id) {
+            Some(existing) => *existing = lock,
+            None => locks.push(lock),
+        }
+    }
+
+    /// Like `set_lock`, but an existing lock named `id` only grows: it keeps the larger of the two
+    /// amounts and the reasons of both.
+    pub fn extend_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        let existing = self.locks(who).iter().find(|lock| lock.id == id).cloned();
+        match existing {
+            Some(lock) => {
+                let amount = if amount > lock.amount { amount } else { lock.amount };
+                self.set_lock(id, who, amount, lock.reasons.union(reasons));
+            },
+            None => self.set_lock(id, who, amount, reasons),
+        }
+    }
+
+    /// Remove the lock named `id` from `who`.
+    pub fn remove_lock(&mut self, id: LockIdentifier, who: &T::AccountId) {
+        if let Some(locks) = self.locks.get_mut(who) {
+            locks.retain(|lock| lock.id != id);
+            if locks.is_empty() {
+                self.locks.remove(who);
+            }
+        }
+    }
+
+    /// The part of the free balance of `who` which can not be withdrawn for `reasons`.
+    pub fn frozen_balance(&self, who: &T::AccountId, reasons: WithdrawReasons) -> T::Balance {
+        self.locks(who)
+            .iter()
+            .filter(|lock| lock.reasons.intersects(reasons))
+            .fold(T::Balance::zero(), |max, lock| if lock.amount > max { lock.amount } else { max })
+    }
+
+    // Ensure that leaving `who` with a free balance of `new_balance` respects its locks.
+    fn ensure_can_withdraw(
+        &self,
+        who: &T::AccountId,
+        reasons: WithdrawReasons,
+        new_balance: T::Balance,
+    ) -> DispatchResult {
+        if new_balance < self.frozen_balance(who, reasons) {
+            return Err("funds are locked");
+        }
+        Ok(())
+    }
+
+    /// Move `amount` from `from` to `to`.
+    ///
+    /// The transfer fails if `to` would end up below the existential deposit. If `from` would, it
+    /// is reaped when `existence` allows it, and the transfer fails otherwise. Transferring to
+    /// oneself changes nothing.
+    pub fn do_transfer(
+		&mut self,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+		existence: ExistenceRequirement,
+	) -> DispatchResult {
+		if from == to {
+			return Ok(());
+		}
+		let from_balance = self.balance(from);
+		let to_balance = self.balance(to);
+
+		let new_from_balance = from_balance.checked_sub(&amount).ok_or("Not enough funds.")?;
+		let new_to_balance = to_balance.checked_add(&amount).ok_or("Overflow")?;
+		self.ensure_can_withdraw(from, WithdrawReasons::TRANSFER, new_from_balance)?;
+
+		if new_to_balance.saturating_add(self.reserved_balance(to)) < T::EXISTENTIAL_DEPOSIT {
+			return Err("balance would be below the existential deposit");
+		}
+		if existence == ExistenceRequirement::KeepAlive &&
+			new_from_balance.saturating_add(self.reserved_balance(from)) < T::EXISTENTIAL_DEPOSIT
+		{
+			return Err("transfer would kill the account");
+		}
+
+		self.write_free(from, new_from_balance);
+		self.write_free(to, new_to_balance);
+		self.reap_if_dust(from);
+
+		Ok(())
+	}
+
+	/// Move `amount` of the free balance of `who` to its reserved balance.
+	pub fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let new_free = self.balance(who).checked_sub(&amount).ok_or("Not enough funds.")?;
+		let new_reserved = self.reserved_balance(who).checked_add(&amount).ok_or("Overflow")?;
+		self.ensure_can_withdraw(who, WithdrawReasons::RESERVE, new_free)?;
+
+		self.write_free(who, new_free);
+		self.write_reserved(who, new_reserved);
+		Ok(())
+	}
+
+	/// Move up to `amount` of the reserved balance of `who` back to its free balance, and return
+	/// how much of `amount` was not reserved.
+	pub fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		let reserved = self.reserved_balance(who);
+		let actual = if amount < reserved { amount } else { reserved };
+
+		self.write_reserved(who, reserved.saturating_sub(actual));
+		self.write_free(who, self.balance(who).saturating_add(actual));
+		amount.saturating_sub(actual)
+	}
+
+	/// Destroy up to `amount` of the reserved balance of `who`, and return how much of `amount` was
+	/// not reserved. This may reap the account.
+	pub fn slash_reserved(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		let reserved = self.reserved_balance(who);
+		let actual = if amount < reserved { amount } else { reserved };
+
+		self.write_reserved(who, reserved.saturating_sub(actual));
+		self.total_issuance = self.total_issuance.saturating_sub(actual);
+		self.reap_if_dust(who);
+		amount.saturating_sub(actual)
+	}
+
+	/// Move up to `amount` of the reserved balance of `slashed` to the balance of `beneficiary`
+	/// given by `status`, and return how much of `amount` was not reserved. This may reap
+	/// `slashed`.
+	///
+	/// The `beneficiary` must already have an account.
+	pub fn repatriate_reserved(
+		&mut self,
+		slashed: &T::AccountId,
+		beneficiary: &T::AccountId,
+		amount: T::Balance,
+		status: BalanceStatus,
+	) -> Result<T::Balance, &'static str> {
+		if slashed == beneficiary {
+			return Ok(match status {
+				BalanceStatus::Free => self.unreserve(slashed, amount),
+				BalanceStatus::Reserved => amount.saturating_sub(self.reserved_balance(slashed)),
+			});
+		}
+		if !self.account_exists(beneficiary) {
+			return Err("beneficiary account does not exist");
+		}
+
+		let reserved = self.reserved_balance(slashed);
+		let actual = if amount < reserved { amount } else { reserved };
+		match status {
+			BalanceStatus::Free => {
+				let new_free = self.balance(beneficiary).checked_add(&actual).ok_or("Overflow")?;
+				self.write_free(beneficiary, new_free);
+			},
+			BalanceStatus::Reserved => {
+				let new_reserved =
+					self.reserved_balance(beneficiary).checked_add(&actual).ok_or("Overflow")?;
+				self.write_reserved(beneficiary, new_reserved);
+			},
+		}
+		self.write_reserved(slashed, reserved.saturating_sub(actual));
+		self.reap_if_dust(slashed);
+		Ok(amount.saturating_sub(actual))
+	}
+
+	/// Create `amount` new tokens in the account of `who`.
+	pub fn do_mint(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let new_balance = self.balance(who).checked_add(&amount).ok_or("Overflow")?;
+		let new_issuance = self.total_issuance.checked_add(&amount).ok_or("Overflow")?;
+		if new_balance.saturating_add(self.reserved_balance(who)) < T::EXISTENTIAL_DEPOSIT {
+			return Err("balance would be below the existential deposit");
+		}
+
+		self.write_free(who, new_balance);
+		self.total_issuance = new_issuance;
+		Ok(())
+	}
+
+	/// Destroy `amount` tokens from the account of `who`, reaping it if `existence` allows it.
+	pub fn do_burn(
+		&mut self,
+		who: &T::AccountId,
+		amount: T::Balance,
+		existence: ExistenceRequirement,
+	) -> DispatchResult {
+		let new_balance = self.balance(who).checked_sub(&amount).ok_or("Not enough funds.")?;
+		self.ensure_can_withdraw(who, WithdrawReasons::TRANSFER, new_balance)?;
+		if existence == ExistenceRequirement::KeepAlive &&
+			new_balance.saturating_add(self.reserved_balance(who)) < T::EXISTENTIAL_DEPOSIT
+		{
+			return Err("transfer would kill the account");
+		}
+
+		self.set_balance(who, new_balance);
+		Ok(())
+	}
+}
+
+// The balances pallet deposits no events.
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {}
+}
+
+// Other pallets use balances through these traits, which forward to the methods above.
+//
+// Note that the system account of someone reaped through them is left to be cleaned up by
+// `kill_if_reaped`, or by the caller itself through `Currency::account_exists`.
+impl<T: Config> Currency<T::AccountId> for Pallet<T> {
+	type Balance = T::Balance;
+
+	fn minimum_balance() -> T::Balance {
+		T::EXISTENTIAL_DEPOSIT
+	}
+
+	fn total_issuance(&self) -> T::Balance {
+		self.total_issuance
+	}
+
+	fn free_balance(&self, who: &T::AccountId) -> T::Balance {
+		self.balance(who)
+	}
+
+	fn total_balance(&self, who: &T::AccountId) -> T::Balance {
+		Pallet::total_balance(self, who)
+	}
+
+	fn account_exists(&self, who: &T::AccountId) -> bool {
+		Pallet::account_exists(self, who)
+	}
+
+	fn transfer(
+		&mut self,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+		existence: ExistenceRequirement,
+	) -> DispatchResult {
+		self.do_transfer(from, to, amount, existence)
+	}
+
+	fn deposit(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		self.do_mint(who, amount)
+	}
+
+	fn withdraw(
+		&mut self,
+		who: &T::AccountId,
+		amount: T::Balance,
+		existence: ExistenceRequirement,
+	) -> DispatchResult {
+		self.do_burn(who, amount, existence)
+	}
+}
+
+impl<T: Config> ReservableCurrency<T::AccountId> for Pallet<T> {
+	fn reserved_balance(&self, who: &T::AccountId) -> T::Balance {
+		Pallet::reserved_balance(self, who)
+	}
+
+	fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		Pallet::reserve(self, who, amount)
+	}
+
+	fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		Pallet::unreserve(self, who, amount)
+	}
+
+	fn slash_reserved(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		Pallet::slash_reserved(self, who, amount)
+	}
+
+	fn repatriate_reserved(
+		&mut self,
+		slashed: &T::AccountId,
+		beneficiary: &T::AccountId,
+		amount: T::Balance,
+		status: BalanceStatus,
+	) -> Result<T::Balance, &'static str> {
+		Pallet::repatriate_reserved(self, slashed, beneficiary, amount, status)
+	}
+}
+
+impl<T: Config> LockableCurrency<T::AccountId> for Pallet<T> {
+	fn set_lock(
+		&mut self,
+		id: LockIdentifier,
+		who: &T::AccountId,
+		amount: T::Balance,
+		reasons: WithdrawReasons,
+	) {
+		Pallet::set_lock(self, id, who, amount, reasons)
+	}
+
+	fn extend_lock(
+		&mut self,
+		id: LockIdentifier,
+		who: &T::AccountId,
+		amount: T::Balance,
+		reasons: WithdrawReasons,
+	) {
+		Pallet::extend_lock(self, id, who, amount, reasons)
+	}
+
+	fn remove_lock(&mut self, id: LockIdentifier, who: &T::AccountId) {
+		Pallet::remove_lock(self, id, who)
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsMut<crate::system::Pallet<T>>,
+{
+	/// Remove what the system pallet knows about `who` if its account was reaped. Pallets which
+	/// slash or repatriate reserved funds should follow up with this.
+	pub fn kill_if_reaped(runtime: &mut T, who: &T::AccountId) {
+		let balances: &mut Self = runtime.as_mut();
+		if !balances.account_exists(who) {
+			let system: &mut crate::system::Pallet<T> = runtime.as_mut();
+			system.kill_account(who);
+		}
+	}
 }
+
 #[macros::call]
-impl<T: Config> Pallet<T> {
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsMut<crate::system::Pallet<T>>,
+{
+    /// Transfer `amount` to `to`, reaping the caller if it is left below the existential deposit.
     pub fn transfer(
-		&mut self,
+		runtime: &mut T,
 		caller: T::AccountId,
 		to: T::AccountId,
 		amount: T::Balance,
-	) -> crate::support::DispatchResult {
-		let caller_balance = self.balance(&caller);
-		let to_balance = self.balance(&to);
-
-		let new_caller_balance = caller_balance.checked_sub(&amount).ok_or("Not enough funds.")?;
-		let new_to_balance = to_balance.checked_add(&amount).ok_or("Overflow")?;
+	) -> DispatchResult {
+		let balances: &mut Self = runtime.as_mut();
+		balances.do_transfer(&caller, &to, amount, ExistenceRequirement::AllowDeath)?;
+		Self::kill_if_reaped(runtime, &caller);
+		Ok(())
+	}
 
-		self.balances.insert(caller, new_caller_balance);
-		self.balances.insert(to, new_to_balance);
+	/// Transfer `amount` to `to`, failing if the caller would be left below the existential
+	/// deposit.
+	pub fn transfer_keep_alive(
+		runtime: &mut T,
+		caller: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let balances: &mut Self = runtime.as_mut();
+		balances.do_transfer(&caller, &to, amount, ExistenceRequirement::KeepAlive)
+	}
 
+	/// Set the balance of `who` to `amount`, reaping it if that is below the existential deposit.
+	/// Root only.
+	pub fn force_set_balance(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		who: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		ensure_root(origin)?;
+		let balances: &mut Self = runtime.as_mut();
+		balances.set_balance(&who, amount);
+		Self::kill_if_reaped(runtime, &who);
 		Ok(())
 	}
 
+	/// Create `amount` new tokens in the account of `who`. Root only.
+	pub fn mint(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		who: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		ensure_root(origin)?;
+		let balances: &mut Self = runtime.as_mut();
+		balances.do_mint(&who, amount)
+	}
 
+	/// Destroy `amount` of the caller's tokens. With `keep_alive`, this fails rather than reaping
+	/// the caller.
+	pub fn burn(
+		runtime: &mut T,
+		caller: T::AccountId,
+		amount: T::Balance,
+		keep_alive: bool,
+	) -> DispatchResult {
+		let existence = if keep_alive {
+			ExistenceRequirement::KeepAlive
+		} else {
+			ExistenceRequirement::AllowDeath
+		};
+		let balances: &mut Self = runtime.as_mut();
+		balances.do_burn(&caller, amount, existence)?;
+		Self::kill_if_reaped(runtime, &caller);
+		Ok(())
+	}
 }
 
 
 #[cfg(test)]
 mod tests {
+    use crate::{
+        balances,
+        support::{
+            BalanceStatus, Dispatch,
+            ExistenceRequirement::{AllowDeath, KeepAlive},
+            WithdrawReasons,
+        },
+        system,
+    };
+    use num::traits::{Saturating, Zero};
+
     struct TestConfig;
 
     impl crate::system::Config for TestConfig {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeOrigin = crate::system::RawOrigin<String>;
+        type RuntimeCall = ();
+        type BaseCallFilter = crate::support::Everything;
     }
 
     impl super::Config for TestConfig {
         type Balance = u128;
+        const EXISTENTIAL_DEPOSIT: u128 = 10;
+    }
+
+    #[derive(Debug, Clone)]
+    #[macros::runtime]
+    struct TestRuntime {
+        system: system::Pallet<Self>,
+        balances: balances::Pallet<Self>,
+    }
+
+    impl system::Config for TestRuntime {
+        type AccountId = String;
+        type BlockNumber = u32;
+        type Nonce = u32;
+        type RuntimeOrigin = RuntimeOrigin;
+        type RuntimeCall = RuntimeCall;
+        type BaseCallFilter = crate::support::Everything;
+    }
+
+    impl super::Config for TestRuntime {
+        type Balance = u128;
+        const EXISTENTIAL_DEPOSIT: u128 = 10;
+    }
+
+    // The sum of all free and reserved balances should always be the total issuance.
+    fn assert_issuance<T: super::Config>(balances: &super::Pallet<T>) {
+        let sum = balances
+            .balances
+            .values()
+            .chain(balances.reserved.values())
+            .fold(T::Balance::zero(), |sum, b| sum.saturating_add(*b));
+        assert!(sum == balances.total_issuance(), "balances do not add up to the total issuance");
     }
+
     #[test]
     fn init_balances() {
         let mut balances: crate::balances::Pallet<TestConfig> = super::Pallet::<TestConfig>::new();
@@ -87,18 +620,253 @@ mod tests {
 		let mut balances: crate::balances::Pallet<TestConfig> = super::Pallet::<TestConfig>::new();
 
 		assert_eq!(
-			balances.transfer("alice".to_string(), "bob".to_string(), 51),
+			balances.do_transfer(&"alice".to_string(), &"bob".to_string(), 51, AllowDeath),
 			Err("Not enough funds.")
 		);
 
 		balances.set_balance(&"alice".to_string(), 100);
-		assert_eq!(balances.transfer("alice".to_string(), "bob".to_string(), 51), Ok(()));
+		assert_eq!(
+			balances.do_transfer(&"alice".to_string(), &"bob".to_string(), 51, AllowDeath),
+			Ok(())
+		);
 		assert_eq!(balances.balance(&"alice".to_string()), 49);
 		assert_eq!(balances.balance(&"bob".to_string()), 51);
 
 		assert_eq!(
-			balances.transfer("alice".to_string(), "bob".to_string(), 51),
+			balances.do_transfer(&"alice".to_string(), &"bob".to_string(), 51, AllowDeath),
+			Err("Not enough funds.")
+		);
+		assert_issuance(&balances);
+
+		// Transferring to oneself mints nothing.
+		assert_eq!(
+			balances.do_transfer(&"alice".to_string(), &"alice".to_string(), 49, AllowDeath),
+			Ok(())
+		);
+		assert_eq!(balances.balance(&"alice".to_string()), 49);
+		assert_issuance(&balances);
+	}
+
+	#[test]
+	fn existential_deposit() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice".to_string();
+		let bob = "bob".to_string();
+
+		// Accounts below the existential deposit do not exist.
+		balances.set_balance(&alice, 5);
+		assert!(!balances.account_exists(&alice));
+		balances.set_balance(&alice, 100);
+		assert!(balances.account_exists(&alice));
+
+		// New accounts need to get at least the existential deposit.
+		assert_eq!(
+			balances.do_transfer(&alice, &bob, 9, AllowDeath),
+			Err("balance would be below the existential deposit")
+		);
+		assert_eq!(balances.do_transfer(&alice, &bob, 10, AllowDeath), Ok(()));
+
+		// Keeping the sender alive refuses to leave dust behind.
+		assert_eq!(
+			balances.do_transfer(&alice, &bob, 85, KeepAlive),
+			Err("transfer would kill the account")
+		);
+		assert_eq!(balances.do_transfer(&alice, &bob, 80, KeepAlive), Ok(()));
+		assert_eq!(balances.balance(&alice), 10);
+
+		// Otherwise the dust is lost and the account is reaped.
+		balances.set_balance(&alice, 15);
+		assert_eq!(balances.do_transfer(&alice, &bob, 10, AllowDeath), Ok(()));
+		assert_eq!(balances.balance(&alice), 0);
+		assert!(!balances.account_exists(&alice));
+		assert_eq!(balances.balance(&bob), 100);
+		assert_eq!(balances.total_issuance(), 100);
+		assert_issuance(&balances);
+	}
+
+	#[test]
+	fn reaping_kills_the_system_account() {
+		let mut runtime = TestRuntime::new();
+		let alice = "alice".to_string();
+		let bob = "bob".to_string();
+		runtime.balances.set_balance(&alice, 100);
+		runtime.system.inc_nonce(&alice);
+
+		let transfer = |amount| super::Call::transfer { to: "bob".to_string(), amount }.into();
+		let keep_alive =
+			|amount| super::Call::transfer_keep_alive { to: "bob".to_string(), amount }.into();
+
+		assert_eq!(runtime.dispatch(RuntimeOrigin::signed(alice.clone()), transfer(50)), Ok(()));
+		assert_eq!(runtime.system.nonce(&alice), 1);
+
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed(alice.clone()), keep_alive(45)),
+			Err("transfer would kill the account")
+		);
+		assert_eq!(runtime.dispatch(RuntimeOrigin::signed(alice.clone()), transfer(45)), Ok(()));
+		assert!(!runtime.balances.account_exists(&alice));
+		assert_eq!(runtime.system.nonce(&alice), 0);
+		assert_eq!(runtime.balances.balance(&bob), 95);
+
+		// Setting a balance below the existential deposit reaps the account too.
+		runtime.system.inc_nonce(&bob);
+		let set_balance =
+			RuntimeCall::balances(super::Call::force_set_balance { who: bob.clone(), amount: 1 });
+		assert_eq!(runtime.dispatch(RuntimeOrigin::root(), set_balance), Ok(()));
+		assert!(!runtime.balances.account_exists(&bob));
+		assert_eq!(runtime.system.nonce(&bob), 0);
+		assert_issuance(&runtime.balances);
+	}
+
+	#[test]
+	fn mint_and_burn() {
+		let mut runtime = TestRuntime::new();
+		let alice = "alice".to_string();
+		runtime.balances.set_balance(&alice, 100);
+		runtime.system.inc_nonce(&alice);
+		assert_eq!(runtime.balances.total_issuance(), 100);
+
+		let mint = |amount| super::Call::mint { who: "alice".to_string(), amount }.into();
+		let burn = |amount, keep_alive| super::Call::burn { amount, keep_alive }.into();
+
+		// Only root can mint.
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed(alice.clone()), mint(50)),
+			Err("bad origin: expected root")
+		);
+		assert_eq!(runtime.dispatch(RuntimeOrigin::root(), mint(50)), Ok(()));
+		assert_eq!(runtime.dispatch(RuntimeOrigin::root(), mint(u128::MAX)), Err("Overflow"));
+		assert_eq!(runtime.balances.balance(&alice), 150);
+		assert_eq!(runtime.balances.total_issuance(), 150);
+		assert_issuance(&runtime.balances);
+
+		// New accounts need to be minted at least the existential deposit.
+		let mint_bob = super::Call::mint { who: "bob".to_string(), amount: 5 }.into();
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::root(), mint_bob),
+			Err("balance would be below the existential deposit")
+		);
+
+		assert_eq!(runtime.dispatch(RuntimeOrigin::signed(alice.clone()), burn(40, true)), Ok(()));
+		assert_eq!(runtime.balances.total_issuance(), 110);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed(alice.clone()), burn(105, true)),
+			Err("transfer would kill the account")
+		);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed(alice.clone()), burn(111, false)),
 			Err("Not enough funds.")
 		);
+
+		// Burning below the existential deposit reaps the account, and the dust with it.
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed(alice.clone()), burn(105, false)),
+			Ok(())
+		);
+		assert!(!runtime.balances.account_exists(&alice));
+		assert_eq!(runtime.system.nonce(&alice), 0);
+		assert_eq!(runtime.balances.total_issuance(), 0);
+		assert_issuance(&runtime.balances);
+	}
+
+	#[test]
+	fn reserved_balances() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice".to_string();
+		let bob = "bob".to_string();
+		balances.set_balance(&alice, 100);
+		balances.set_balance(&bob, 100);
+
+		assert_eq!(balances.reserve(&alice, 101), Err("Not enough funds."));
+		assert_eq!(balances.reserve(&alice, 95), Ok(()));
+		assert_eq!(balances.balance(&alice), 5);
+		assert_eq!(balances.reserved_balance(&alice), 95);
+		assert_eq!(balances.total_balance(&alice), 100);
+
+		// Reserved funds can not be spent, but they keep the account alive.
+		assert_eq!(balances.do_transfer(&alice, &bob, 10, AllowDeath), Err("Not enough funds."));
+		assert_eq!(balances.do_transfer(&alice, &bob, 5, KeepAlive), Ok(()));
+		assert!(balances.account_exists(&alice));
+
+		// Only what is reserved can be unreserved.
+		assert_eq!(balances.unreserve(&alice, 25), 0);
+		assert_eq!(balances.unreserve(&alice, 100), 30);
+		assert_eq!(balances.balance(&alice), 95);
+		assert_eq!(balances.reserved_balance(&alice), 0);
+		assert_eq!(balances.total_issuance(), 200);
+		assert_issuance(&balances);
+	}
+
+	#[test]
+	fn slash_and_repatriate_reserved() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice".to_string();
+		let bob = "bob".to_string();
+		balances.set_balance(&alice, 100);
+		balances.set_balance(&bob, 100);
+		assert_eq!(balances.reserve(&alice, 100), Ok(()));
+
+		// Slashing destroys the funds.
+		assert_eq!(balances.slash_reserved(&alice, 20), 0);
+		assert_eq!(balances.reserved_balance(&alice), 80);
+		assert_eq!(balances.total_issuance(), 180);
+
+		// Repatriating moves them to the beneficiary, who must exist.
+		assert_eq!(
+			balances.repatriate_reserved(&alice, &"charlie".to_string(), 10, BalanceStatus::Free),
+			Err("beneficiary account does not exist")
+		);
+		assert_eq!(balances.repatriate_reserved(&alice, &bob, 30, BalanceStatus::Free), Ok(0));
+		assert_eq!(balances.repatriate_reserved(&alice, &bob, 20, BalanceStatus::Reserved), Ok(0));
+		assert_eq!(balances.balance(&bob), 130);
+		assert_eq!(balances.reserved_balance(&bob), 20);
+		assert_eq!(balances.reserved_balance(&alice), 30);
+		assert_issuance(&balances);
+
+		// Slashing the account below the existential deposit reaps it.
+		assert_eq!(balances.slash_reserved(&alice, 25), 0);
+		assert!(!balances.account_exists(&alice));
+		assert_eq!(balances.reserved_balance(&alice), 0);
+		assert_eq!(balances.slash_reserved(&alice, 10), 10);
+		assert_eq!(balances.total_issuance(), 150);
+		assert_issuance(&balances);
+	}
+
+	#[test]
+	fn overlapping_locks() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice".to_string();
+		let bob = "bob".to_string();
+		balances.set_balance(&alice, 100);
+
+		balances.set_lock(*b"staking ", &alice, 50, WithdrawReasons::ALL);
+		balances.set_lock(*b"vesting ", &alice, 70, WithdrawReasons::TRANSFER);
+		assert_eq!(balances.locks(&alice).len(), 2);
+
+		// Locks overlap, so only the largest one applies.
+		assert_eq!(balances.frozen_balance(&alice, WithdrawReasons::TRANSFER), 70);
+		assert_eq!(balances.frozen_balance(&alice, WithdrawReasons::RESERVE), 50);
+		assert_eq!(balances.do_transfer(&alice, &bob, 31, AllowDeath), Err("funds are locked"));
+		assert_eq!(balances.do_transfer(&alice, &bob, 30, AllowDeath), Ok(()));
+		assert_eq!(balances.do_transfer(&alice, &bob, 100, AllowDeath), Err("Not enough funds."));
+		assert_eq!(balances.do_burn(&alice, 1, AllowDeath), Err("funds are locked"));
+
+		// The vesting lock does not restrict reserving.
+		balances.set_balance(&alice, 100);
+		assert_eq!(balances.reserve(&alice, 51), Err("funds are locked"));
+		assert_eq!(balances.reserve(&alice, 50), Ok(()));
+		assert_eq!(balances.unreserve(&alice, 50), 0);
+
+		// Extending keeps the larger amount and all reasons, setting replaces the lock.
+		balances.extend_lock(*b"vesting ", &alice, 60, WithdrawReasons::RESERVE);
+		assert_eq!(balances.frozen_balance(&alice, WithdrawReasons::RESERVE), 70);
+		balances.set_lock(*b"vesting ", &alice, 20, WithdrawReasons::TRANSFER);
+		assert_eq!(balances.frozen_balance(&alice, WithdrawReasons::TRANSFER), 50);
+
+		balances.remove_lock(*b"staking ", &alice);
+		balances.set_lock(*b"vesting ", &alice, 0, WithdrawReasons::ALL);
+		assert!(balances.locks(&alice).is_empty());
+		assert_eq!(balances.do_transfer(&alice, &bob, 100, AllowDeath), Ok(()));
+		assert_issuance(&balances);
 	}
 }
@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	support::{ClearEvents, Currency, DispatchResult, ValidateUnsigned},
+	system::ensure_none,
+};
+
+/// The balance type of the currency the faucet drips.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as crate::system::Config>::AccountId>>::Balance;
+
+pub trait Config: crate::system::Config {
+	/// The currency the faucet drips, usually the `balances` pallet.
+	type Currency: Currency<Self::AccountId>;
+	/// How much new currency each drip gives.
+	const DRIP_AMOUNT: BalanceOf<Self>;
+	/// How many blocks an account has to wait between drips.
+	const DRIP_INTERVAL: Self::BlockNumber;
+}
+
+/// The events emitted by the faucet pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+	/// `amount` was dripped into the account `who`.
+	Dripped { who: T::AccountId, amount: BalanceOf<T> },
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The block each account was last dripped in.
+	last_drip: BTreeMap<T::AccountId, T::BlockNumber>,
+	events: Vec<Event<T>>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { last_drip: BTreeMap::new(), events: Vec::new() }
+	}
+
+	/// The block `who` was last dripped in, if any.
+	pub fn last_drip(&self, who: &T::AccountId) -> Option<T::BlockNumber> {
+		self.last_drip.get(who).copied()
+	}
+
+	/// The events deposited so far.
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+
+	// Ensure that `who` waited long enough since its last drip to be dripped in the block `now`.
+	fn ensure_can_drip(&self, who: &T::AccountId, now: T::BlockNumber) -> DispatchResult {
+		if let Some(mut next) = self.last_drip(who) {
+			next += T::DRIP_INTERVAL;
+			if now < next {
+				return Err("account was dripped too recently");
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+// Drips are unsigned, since the accounts asking for them have no funds to pay with, so the faucet
+// decides which drips to let through itself.
+impl<T: Config> ValidateUnsigned<T> for Pallet<T>
+where
+	T: AsRef<Pallet<T>> + AsRef<crate::system::Pallet<T>>,
+{
+	type Call = Call<T>;
+
+	fn validate_unsigned(runtime: &T, call: &Call<T>) -> DispatchResult {
+		match call {
+			Call::drip { who } => {
+				let system: &crate::system::Pallet<T> = runtime.as_ref();
+				let faucet: &Self = runtime.as_ref();
+				faucet.ensure_can_drip(who, system.block_number())
+			},
+			_ => Err("call can not be unsigned"),
+		}
+	}
+}
+
+// Drips reach the currency through `T::Currency` to mint it, and the system pallet to learn the
+// current block.
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsMut<T::Currency> + AsRef<crate::system::Pallet<T>>,
+{
+	/// Give `DRIP_AMOUNT` of new currency to `who`, at most once every `DRIP_INTERVAL` blocks.
+	///
+	/// This is unsigned, so that accounts with no funds can use it.
+	pub fn drip(runtime: &mut T, origin: T::RuntimeOrigin, who: T::AccountId) -> DispatchResult {
+		ensure_none(origin)?;
+		let system: &crate::system::Pallet<T> = runtime.as_ref();
+		let now = system.block_number();
+		let faucet: &mut Self = runtime.as_mut();
+		faucet.ensure_can_drip(&who, now)?;
+
+		let currency: &mut T::Currency = runtime.as_mut();
+		currency.deposit(&who, T::DRIP_AMOUNT)?;
+
+		let faucet: &mut Self = runtime.as_mut();
+		faucet.last_drip.insert(who.clone(), now);
+		faucet.deposit_event(Event::Dripped { who, amount: T::DRIP_AMOUNT });
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		balances, faucet,
+		support::{Block, Dispatch, Extrinsic, Header},
+		system,
+	};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		#[unsigned]
+		faucet: faucet::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	impl super::Config for TestRuntime {
+		type Currency = balances::Pallet<Self>;
+		const DRIP_AMOUNT: u128 = 50;
+		const DRIP_INTERVAL: u32 = 2;
+	}
+
+	fn unsigned(call: impl Into<RuntimeCall>) -> Extrinsic<String, RuntimeCall> {
+		Extrinsic::Unsigned { call: call.into() }
+	}
+
+	fn drip(who: &str) -> Extrinsic<String, RuntimeCall> {
+		unsigned(super::Call::drip { who: who.to_string() })
+	}
+
+	fn execute(runtime: &mut TestRuntime, extrinsics: Vec<Extrinsic<String, RuntimeCall>>) {
+		let header = Header { block_number: runtime.system.block_number() + 1 };
+		assert_eq!(runtime.execute_block(Block { header, extrinsics }), Ok(()));
+	}
+
+	#[test]
+	fn drips_are_unsigned() {
+		let mut runtime = TestRuntime::new();
+		let alice = "alice".to_string();
+
+		// Signed drips are rejected.
+		assert_eq!(
+			runtime.dispatch(
+				RuntimeOrigin::signed(alice.clone()),
+				super::Call::drip { who: alice.clone() }.into()
+			),
+			Err("bad origin: expected none")
+		);
+
+		// Only one of these is let through, and no nonce is used.
+		execute(&mut runtime, vec![drip("alice"), drip("alice")]);
+		assert_eq!(runtime.balances.balance(&alice), 50);
+		assert_eq!(runtime.faucet.last_drip(&alice), Some(1));
+		assert_eq!(runtime.system.nonce(&alice), 0);
+		assert!(matches!(
+			runtime.faucet.events(),
+			[super::Event::Dripped { who, amount: 50 }] if who == "alice"
+		));
+
+		// Alice has to wait for the interval to pass.
+		execute(&mut runtime, vec![drip("alice")]);
+		assert_eq!(runtime.balances.balance(&alice), 50);
+		execute(&mut runtime, vec![drip("alice")]);
+		assert_eq!(runtime.balances.balance(&alice), 100);
+		assert_eq!(runtime.faucet.last_drip(&alice), Some(3));
+	}
+
+	#[test]
+	fn unsigned_calls_need_approval() {
+		let mut runtime = TestRuntime::new();
+		let alice = "alice".to_string();
+
+		// The validation of the pallet is asked.
+		runtime.faucet.last_drip.insert(alice.clone(), 1);
+		let call = super::Call::drip { who: alice.clone() }.into();
+		assert_eq!(runtime.validate_unsigned(&call), Err("account was dripped too recently"));
+
+		// Pallets which are not marked with `#[unsigned]` approve no unsigned calls.
+		let force = balances::Call::force_set_balance { who: alice.clone(), amount: 100 };
+		assert_eq!(
+			runtime.validate_unsigned(&force.clone().into()),
+			Err("unsigned calls to this pallet are not allowed")
+		);
+		execute(&mut runtime, vec![unsigned(force)]);
+		assert_eq!(runtime.balances.balance(&alice), 0);
+	}
+}
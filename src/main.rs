@@ -1,9 +1,18 @@
+mod assets;
 mod balances;
+mod faucet;
+mod multisig;
+mod nfts;
 mod proof_of_existence;
+mod proxy;
+mod scheduler;
+mod sudo;
 mod system;
 mod support;
-
-use crate::support::Dispatch;
+mod timestamp;
+mod tx_pause;
+mod utility;
+mod vesting;
 
 
 mod types {
@@ -15,29 +24,162 @@ mod types {
     pub type Header = crate::support::Header<BlockNumber>;
     pub type Block = crate::support::Block<Header, Extrinsic>;
     pub type Content = String;
+    pub type AssetId = u32;
+    pub type CollectionId = u32;
+    pub type ItemId = u32;
+    pub type Moment = u64;
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[macros::runtime]
 pub struct Runtime {
     system: system::Pallet<Self>,
+    #[hooks]
+    #[inherent]
+    timestamp: timestamp::Pallet<Self>,
     balances: balances::Pallet<Self>,
+    #[unsigned]
+    faucet: faucet::Pallet<Self>,
+    assets: assets::Pallet<Self>,
+    nfts: nfts::Pallet<Self>,
+    #[hooks]
     proof_of_existence: proof_of_existence::Pallet<Self>,
+    sudo: sudo::Pallet<Self>,
+    utility: utility::Pallet<Self>,
+    #[hooks]
+    scheduler: scheduler::Pallet<Self>,
+    vesting: vesting::Pallet<Self>,
+    multisig: multisig::Pallet<Self>,
+    proxy: proxy::Pallet<Self>,
+    tx_pause: tx_pause::Pallet<Self>,
 }
 
 impl system::Config for Runtime {
     type AccountId = types::AccountId;
     type BlockNumber = types::BlockNumber;
     type Nonce = types::Nonce;
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type BaseCallFilter = (tx_pause::Pallet<Self>, proxy::Pallet<Self>);
+}
+
+impl timestamp::Config for Runtime {
+    type Moment = types::Moment;
+    const MINIMUM_PERIOD: types::Moment = 3_000;
+    const MAX_DRIFT: types::Moment = 30_000;
 }
 
 impl balances::Config for Runtime {
     type Balance = types::Balance;
+    const EXISTENTIAL_DEPOSIT: types::Balance = 1;
+}
+
+impl faucet::Config for Runtime {
+    type Currency = balances::Pallet<Self>;
+    const DRIP_AMOUNT: types::Balance = 10;
+    const DRIP_INTERVAL: types::BlockNumber = 100;
+}
+
+impl assets::Config for Runtime {
+    type AssetId = types::AssetId;
+    type AssetBalance = types::Balance;
+}
+
+impl nfts::Config for Runtime {
+    type CollectionId = types::CollectionId;
+    type ItemId = types::ItemId;
 }
 
 impl proof_of_existence::Config for Runtime {
     type Content = types::Content;
+    type Hasher = support::StdHasher;
+    type Currency = balances::Pallet<Self>;
+    const CLAIM_DEPOSIT: types::Balance = 10;
+    const CLAIM_DURATION: Option<types::BlockNumber> = Some(100);
+    const MAX_EXPIRED_PER_BLOCK: u32 = 10;
+    const MAX_CLAIMS_PER_ACCOUNT: u32 = 100;
+}
+
+impl sudo::Config for Runtime {}
+
+impl utility::Config for Runtime {}
+
+impl vesting::Config for Runtime {
+    type Balance = types::Balance;
+    type Currency = balances::Pallet<Self>;
+    const MIN_VESTED_TRANSFER: types::Balance = 10;
+    const MAX_VESTING_SCHEDULES: u32 = 28;
+}
+
+impl multisig::Config for Runtime {
+    type Hasher = support::StdHasher;
+    type Currency = balances::Pallet<Self>;
+    const DEPOSIT: types::Balance = 10;
+    const MAX_SIGNATORIES: u32 = 100;
+}
+
+/// What the proxies of an account may do for it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProxyType {
+    /// Any call.
+    Any,
+    /// Only calls to `balances`.
+    Balances,
+    /// Only calls to `proof_of_existence`.
+    ProofOfExistence,
+}
+
+impl support::Encode for ProxyType {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.push(self.clone() as u8);
+    }
+}
+
+impl support::InstanceFilter<RuntimeCall> for ProxyType {
+    fn filter(&self, call: &RuntimeCall) -> bool {
+        match self {
+            ProxyType::Any => true,
+            ProxyType::Balances => matches!(call, RuntimeCall::balances(_)),
+            ProxyType::ProofOfExistence => matches!(call, RuntimeCall::proof_of_existence(_)),
+        }
+    }
+}
+
+impl proxy::Config for Runtime {
+    type ProxyType = ProxyType;
+    type Hasher = support::StdHasher;
+    type Currency = balances::Pallet<Self>;
+    const PROXY_DEPOSIT: types::Balance = 10;
+    const MAX_PROXIES: u32 = 32;
+    const MAX_PENDING: u32 = 32;
+}
+
+impl tx_pause::Config for Runtime {
+    // Pausing the time would make every block invalid, and pausing the others could not be undone.
+    const WHITELISTED_PALLETS: &'static [&'static str] =
+        &["system", "timestamp", "sudo", "tx_pause"];
+}
+
+impl scheduler::Config for Runtime {
+    const MAXIMUM_WEIGHT: support::Weight = 1_000;
+    const MAX_SCHEDULED_PER_BLOCK: u32 = 50;
+
+    fn call_weight(_call: &RuntimeCall) -> support::Weight {
+        100
+    }
+}
+
+// The inherent data of a node at the time `now`.
+fn inherent_data(now: types::Moment) -> support::InherentData {
+    let mut data = support::InherentData::new();
+    data.put(<timestamp::Pallet<Runtime> as support::ProvideInherent>::INHERENT_IDENTIFIER, now);
+    data
+}
+
+// The inherents the author of a block made at the time `now` puts at its start.
+fn inherents(now: types::Moment) -> Vec<types::Extrinsic> {
+    Runtime::create_inherents(&inherent_data(now))
 }
 
 
@@ -51,34 +193,57 @@ fn main() {
 	let charlie = "charlie".to_string();
 
 	runtime.balances.set_balance(&alice, 100);
+	runtime.sudo.initialize_key(alice.clone());
 
-	let block_1 = types::Block {
+	let mut block_1 = types::Block {
         header: support::Header { block_number: 1},
         extrinsics: vec![
-            support::Extrinsic {
+            support::Extrinsic::Signed {
                 caller: alice.clone(),
                 call: RuntimeCall::balances(balances::Call::transfer{ 
                     to: bob.clone(), 
                     amount: 30
                 }),
             },
-            support::Extrinsic {
+            support::Extrinsic::Signed {
                 caller: alice.clone(),
                 call: RuntimeCall::balances(balances::Call::transfer{to: charlie, amount: 20}),
             },
+            support::Extrinsic::Signed {
+                caller: alice.clone(),
+                call: RuntimeCall::assets(assets::Call::create {
+                    asset_id: 1,
+                    admin: alice.clone(),
+                    min_balance: 1,
+                }),
+            },
+            support::Extrinsic::Signed {
+                caller: alice.clone(),
+                call: RuntimeCall::assets(assets::Call::mint {
+                    asset_id: 1,
+                    beneficiary: bob.clone(),
+                    amount: 1_000,
+                }),
+            },
+            support::Extrinsic::Signed {
+                caller: alice.clone(),
+                call: RuntimeCall::system(system::Call::remark_with_event {
+                    remark: b"Hello, world!".to_vec(),
+                }),
+            },
         ],
     };
 
-    let block_2 = types::Block {
+    let mut block_2 = types::Block {
         header: support::Header { block_number: 2 },
         extrinsics: vec![
-            support::Extrinsic {
+            support::Extrinsic::Signed {
                 caller: alice.clone(),
                 call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim { 
                     claim: "Hello, world!".to_string() 
                 }),
             },
-            support::Extrinsic {
+            support::Extrinsic::Signed {
                 caller: bob.clone(),
                 call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim { 
                     claim: "Hello, world!".to_string() 
@@ -87,28 +252,46 @@ fn main() {
         ],
     };
 
-    let block_3 = types::Block {
+    let mut block_3 = types::Block {
         header: support::Header { block_number: 3 },
         extrinsics: vec![
-            support::Extrinsic {
-                caller: alice,
-                call: RuntimeCall::proof_of_existence(proof_of_existence::Call::revoke_claim { 
-                    claim: "Hello, world!".to_string(), 
+            support::Extrinsic::Signed {
+                caller: alice.clone(),
+                call: RuntimeCall::proof_of_existence(proof_of_existence::Call::revoke_claim {
+                    hash: proof_of_existence::Pallet::<Runtime>::hash_of(b"Hello, world!"),
                 }),
             },
-            support::Extrinsic {
-                caller: bob,
+            support::Extrinsic::Signed {
+                caller: bob.clone(),
                 call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim { 
                     claim: "Hello, world!".to_string(), 
                 }),
             },
+            support::Extrinsic::Unsigned {
+                call: RuntimeCall::faucet(faucet::Call::drip { who: "dave".to_string() }),
+            },
+            support::Extrinsic::Signed {
+                caller: alice,
+                call: RuntimeCall::sudo(sudo::Call::sudo {
+                    call: Box::new(RuntimeCall::balances(balances::Call::force_set_balance {
+                        who: bob,
+                        amount: 100,
+                    })),
+                }),
+            },
         ]
     };
 
 
+    // Every block starts with its inherents, e.g. its time.
+    for (block, now) in [(&mut block_1, 6_000), (&mut block_2, 12_000), (&mut block_3, 18_000)] {
+        block.extrinsics.splice(0..0, inherents(now));
+    }
+
     runtime.execute_block(block_1).expect("invalid block");
     runtime.execute_block(block_2).expect("invalid block");
-    runtime.execute_block(block_3).expect("invalid block");
+    // The last block was made by someone else, so its inherents are checked against our own data.
+    runtime.import_block(block_3, &inherent_data(18_000)).expect("invalid block");
 
     println!("{:#?}", runtime);
 }
\ No newline at end of file
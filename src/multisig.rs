@@ -0,0 +1,567 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	support::{
+		self, ClearEvents, Currency, DispatchResult, Dispatchable, Encode, FromHash, Hasher,
+		ReservableCurrency,
+	},
+	system::RawOrigin,
+};
+
+/// The balance type of the currency used for multisig deposits.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as crate::system::Config>::AccountId>>::Balance;
+
+/// The hash calls are approved by, which is `support::hash_of` with the `Hasher` of the runtime.
+pub type CallHashOf<T> = <<T as Config>::Hasher as Hasher>::Output;
+
+/// Multisig accounts are derived from the hash of their signatories, and calls are approved by
+/// their hash, so account ids and calls have to be encodable, and account ids made from a hash.
+pub trait Config:
+	crate::system::RuntimeCallConfig<AccountId: Encode + FromHash, RuntimeCall: Encode>
+{
+	/// How multisig accounts and calls are hashed.
+	type Hasher: Hasher;
+	/// The currency deposits for pending operations are reserved in, usually the `balances`
+	/// pallet.
+	type Currency: ReservableCurrency<Self::AccountId>;
+	/// The deposit reserved from whoever starts an operation, until it is executed or cancelled.
+	const DEPOSIT: BalanceOf<Self>;
+	/// The maximum number of signatories of a multisig account, including the caller.
+	const MAX_SIGNATORIES: u32;
+}
+
+/// A point in the history of the chain: an extrinsic of some block. Pending operations are
+/// identified by the point they were started at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timepoint<BlockNumber> {
+	/// The block the extrinsic is in.
+	pub height: BlockNumber,
+	/// The index of the extrinsic in its block.
+	pub index: u32,
+}
+
+impl<BlockNumber: Encode> Encode for Timepoint<BlockNumber> {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.height.encode_to(dest);
+		self.index.encode_to(dest);
+	}
+}
+
+/// The events emitted by the multisig pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+	/// `approving` started an operation for `multisig`.
+	NewMultisig { approving: T::AccountId, multisig: T::AccountId, call_hash: CallHashOf<T> },
+	/// `approving` approved the operation of `multisig` started at `timepoint`.
+	MultisigApproval {
+		approving: T::AccountId,
+		timepoint: Timepoint<T::BlockNumber>,
+		multisig: T::AccountId,
+		call_hash: CallHashOf<T>,
+	},
+	/// The operation of `multisig` started at `timepoint` was dispatched, with the given result.
+	MultisigExecuted {
+		approving: T::AccountId,
+		timepoint: Timepoint<T::BlockNumber>,
+		multisig: T::AccountId,
+		call_hash: CallHashOf<T>,
+		result: DispatchResult,
+	},
+	/// `cancelling` cancelled the operation of `multisig` started at `timepoint`.
+	MultisigCancelled {
+		cancelling: T::AccountId,
+		timepoint: Timepoint<T::BlockNumber>,
+		multisig: T::AccountId,
+		call_hash: CallHashOf<T>,
+	},
+}
+
+/// An operation of a multisig account waiting for approvals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Multisig<AccountId, Balance, BlockNumber> {
+	/// When the operation was started.
+	pub when: Timepoint<BlockNumber>,
+	/// The deposit reserved from `depositor`.
+	pub deposit: Balance,
+	/// Who started the operation, and can cancel it.
+	pub depositor: AccountId,
+	/// The signatories who approved the operation so far, sorted.
+	pub approvals: Vec<AccountId>,
+}
+
+/// The pending operations of a given runtime.
+pub type MultisigOf<T> = Multisig<
+	<T as crate::system::Config>::AccountId,
+	BalanceOf<T>,
+	<T as crate::system::Config>::BlockNumber,
+>;
+
+// What a signatory brings to an operation: the call itself, or only the hash of it.
+enum CallOrHash<Call, Hash> {
+	Call(Call),
+	Hash(Hash),
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The pending operations of each multisig account, keyed by the hash of their call.
+	#[allow(clippy::type_complexity)]
+	multisigs: BTreeMap<
+		(T::AccountId, <T::Hasher as Hasher>::Output),
+		Multisig<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+	>,
+	events: Vec<Event<T>>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { multisigs: BTreeMap::new(), events: Vec::new() }
+	}
+
+	/// The account of the signatories `who` with `threshold`, in any order. Any `threshold` of them
+	/// can dispatch calls from it.
+	pub fn multi_account_id(who: &[T::AccountId], threshold: u16) -> T::AccountId {
+		let mut signatories = who.to_vec();
+		signatories.sort();
+		let hash = support::hash_of::<T::Hasher, _>(&(&b"multisig"[..], signatories, threshold));
+		T::AccountId::from_hash(&hash.encode())
+	}
+
+	/// The pending operation of `multisig` with the call hashed to `call_hash`, if any.
+	pub fn multisig(
+		&self,
+		multisig: &T::AccountId,
+		call_hash: CallHashOf<T>,
+	) -> Option<&MultisigOf<T>> {
+		self.multisigs.get(&(multisig.clone(), call_hash))
+	}
+
+	/// The events deposited so far.
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+
+	// The multisig account of `caller` and `other_signatories` with `threshold`, as long as they
+	// make sense together.
+	fn ensure_multisig(
+		caller: &T::AccountId,
+		threshold: u16,
+		other_signatories: &[T::AccountId],
+	) -> Result<T::AccountId, &'static str> {
+		if threshold < 2 {
+			return Err("threshold must be 2 or more");
+		}
+		let mut signatories = other_signatories.to_vec();
+		signatories.push(caller.clone());
+		signatories.sort();
+		signatories.dedup();
+		if signatories.len() != other_signatories.len() + 1 {
+			return Err("signatories must be unique, and not include the caller");
+		}
+		if signatories.len() > T::MAX_SIGNATORIES as usize {
+			return Err("too many signatories");
+		}
+		if usize::from(threshold) > signatories.len() {
+			return Err("threshold is higher than the number of signatories");
+		}
+		Ok(Self::multi_account_id(&signatories, threshold))
+	}
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsMut<T::Currency> + AsRef<crate::system::Pallet<T>>,
+{
+	// Approve an operation, starting it if it is new, and dispatch its call if it has enough
+	// approvals and the call is given.
+	fn operate(
+		runtime: &mut T,
+		caller: T::AccountId,
+		threshold: u16,
+		other_signatories: Vec<T::AccountId>,
+		maybe_timepoint: Option<Timepoint<T::BlockNumber>>,
+		call_or_hash: CallOrHash<T::RuntimeCall, CallHashOf<T>>,
+	) -> DispatchResult {
+		let multisig = Self::ensure_multisig(&caller, threshold, &other_signatories)?;
+		let (call, call_hash) = match call_or_hash {
+			CallOrHash::Call(call) => {
+				let hash = support::hash_of::<T::Hasher, _>(&call);
+				(Some(call), hash)
+			},
+			CallOrHash::Hash(hash) => (None, hash),
+		};
+		let key = (multisig.clone(), call_hash.clone());
+		let pallet: &mut Self = runtime.as_mut();
+
+		let Some(pending) = pallet.multisigs.get_mut(&key) else {
+			// A new operation.
+			if maybe_timepoint.is_some() {
+				return Err("a new operation must not have a timepoint");
+			}
+			let system: &crate::system::Pallet<T> = runtime.as_ref();
+			let when = Timepoint { height: system.block_number(), index: system.extrinsic_index() };
+			let currency: &mut T::Currency = runtime.as_mut();
+			currency.reserve(&caller, T::DEPOSIT)?;
+
+			let pending = Multisig {
+				when,
+				deposit: T::DEPOSIT,
+				depositor: caller.clone(),
+				approvals: vec![caller.clone()],
+			};
+			let pallet: &mut Self = runtime.as_mut();
+			pallet.multisigs.insert(key, pending);
+			pallet.deposit_event(Event::NewMultisig { approving: caller, multisig, call_hash });
+			return Ok(());
+		};
+
+		let timepoint = maybe_timepoint.ok_or("an existing operation needs its timepoint")?;
+		if timepoint != pending.when {
+			return Err("timepoint does not match the operation");
+		}
+		if let Err(index) = pending.approvals.binary_search(&caller) {
+			pending.approvals.insert(index, caller.clone());
+		} else if call.is_none() || pending.approvals.len() < usize::from(threshold) {
+			return Err("operation was already approved by the caller");
+		}
+
+		let Some(call) = call.filter(|_| pending.approvals.len() >= usize::from(threshold)) else {
+			let event =
+				Event::MultisigApproval { approving: caller, timepoint, multisig, call_hash };
+			pallet.deposit_event(event);
+			return Ok(());
+		};
+
+		// Enough signatories approved, so the operation is done, whatever the call does.
+		let pending = pallet.multisigs.remove(&key).ok_or("operation does not exist")?;
+		let currency: &mut T::Currency = runtime.as_mut();
+		currency.unreserve(&pending.depositor, pending.deposit);
+		let result = call.dispatch(runtime, RawOrigin::Signed(multisig.clone()).into());
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.deposit_event(Event::MultisigExecuted {
+			approving: caller,
+			timepoint,
+			multisig,
+			call_hash,
+			result,
+		});
+		Ok(())
+	}
+}
+
+#[allow(clippy::boxed_local)]
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsMut<T::Currency> + AsRef<crate::system::Pallet<T>>,
+{
+	/// Approve `call` as one of the signatories of the multisig account of the caller and
+	/// `other_signatories` with `threshold`, and dispatch it from that account if this is the last
+	/// approval it needs.
+	///
+	/// The first approval starts the operation, and reserves a deposit from the caller. The
+	/// others must give the `Timepoint` it was started at.
+	pub fn as_multi(
+		runtime: &mut T,
+		caller: T::AccountId,
+		threshold: u16,
+		other_signatories: Vec<T::AccountId>,
+		maybe_timepoint: Option<Timepoint<T::BlockNumber>>,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let call = CallOrHash::Call(*call);
+		Self::operate(runtime, caller, threshold, other_signatories, maybe_timepoint, call)
+	}
+
+	/// Like `as_multi`, but approve the call by its hash only, so it is never dispatched by this.
+	pub fn approve_as_multi(
+		runtime: &mut T,
+		caller: T::AccountId,
+		threshold: u16,
+		other_signatories: Vec<T::AccountId>,
+		maybe_timepoint: Option<Timepoint<T::BlockNumber>>,
+		call_hash: CallHashOf<T>,
+	) -> DispatchResult {
+		let hash = CallOrHash::Hash(call_hash);
+		Self::operate(runtime, caller, threshold, other_signatories, maybe_timepoint, hash)
+	}
+
+	/// Cancel a pending operation, and return its deposit. Only whoever started it may do this.
+	pub fn cancel_as_multi(
+		runtime: &mut T,
+		caller: T::AccountId,
+		threshold: u16,
+		other_signatories: Vec<T::AccountId>,
+		timepoint: Timepoint<T::BlockNumber>,
+		call_hash: CallHashOf<T>,
+	) -> DispatchResult {
+		let multisig = Self::ensure_multisig(&caller, threshold, &other_signatories)?;
+		let key = (multisig.clone(), call_hash.clone());
+		let pallet: &mut Self = runtime.as_mut();
+		let pending = pallet.multisigs.get(&key).ok_or("operation does not exist")?;
+		if pending.when != timepoint {
+			return Err("timepoint does not match the operation");
+		}
+		if pending.depositor != caller {
+			return Err("only whoever started the operation can cancel it");
+		}
+
+		let pending = pallet.multisigs.remove(&key).ok_or("operation does not exist")?;
+		let currency: &mut T::Currency = runtime.as_mut();
+		currency.unreserve(&pending.depositor, pending.deposit);
+		let pallet: &mut Self = runtime.as_mut();
+		let event = Event::MultisigCancelled { cancelling: caller, timepoint, multisig, call_hash };
+		pallet.deposit_event(event);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Event, Timepoint};
+	use crate::{
+		balances, multisig,
+		support::{Dispatch, Encode, Hasher, StdHasher},
+		system,
+	};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		multisig: multisig::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	impl super::Config for TestRuntime {
+		type Hasher = StdHasher;
+		type Currency = balances::Pallet<Self>;
+		const DEPOSIT: u128 = 10;
+		const MAX_SIGNATORIES: u32 = 3;
+	}
+
+	type Multisig = super::Pallet<TestRuntime>;
+
+	crate::support::test_helpers!();
+
+	fn hash_of(call: &RuntimeCall) -> u64 {
+		crate::support::hash_of::<StdHasher, _>(call)
+	}
+
+	fn accounts(who: &[&str]) -> Vec<String> {
+		who.iter().map(|who| who.to_string()).collect()
+	}
+
+	fn as_multi(
+		others: &[&str],
+		maybe_timepoint: Option<Timepoint<u32>>,
+		call: RuntimeCall,
+	) -> RuntimeCall {
+		let other_signatories = accounts(others);
+		let call = Box::new(call);
+		super::Call::as_multi { threshold: 2, other_signatories, maybe_timepoint, call }.into()
+	}
+
+	fn setup() -> (TestRuntime, String) {
+		let mut runtime = TestRuntime::new();
+		let multisig = Multisig::multi_account_id(&accounts(&["alice", "bob", "charlie"]), 2);
+		for who in ["alice", "bob", "charlie"] {
+			runtime.balances.set_balance(&who.to_string(), 100);
+		}
+		runtime.balances.set_balance(&multisig, 1_000);
+		(runtime, multisig)
+	}
+
+	#[test]
+	fn multisig_accounts_are_deterministic() {
+		let id = Multisig::multi_account_id(&accounts(&["alice", "bob", "charlie"]), 2);
+		assert_eq!(Multisig::multi_account_id(&accounts(&["charlie", "alice", "bob"]), 2), id);
+		assert_ne!(Multisig::multi_account_id(&accounts(&["alice", "bob", "charlie"]), 3), id);
+		assert_ne!(Multisig::multi_account_id(&accounts(&["alice", "bob"]), 2), id);
+	}
+
+	#[test]
+	fn calls_are_approved_by_the_hash_of_their_encoding() {
+		// The index of `balances` in the runtime and of `transfer` in it, then the arguments.
+		let mut encoded = vec![1, 0, 4, 0, 0, 0];
+		encoded.extend(b"dave");
+		encoded.extend(100u128.to_le_bytes());
+		assert_eq!(transfer("dave", 100).encode(), encoded);
+		assert_eq!(hash_of(&transfer("dave", 100)), StdHasher::hash(&encoded));
+		assert_ne!(hash_of(&transfer("dave", 101)), hash_of(&transfer("dave", 100)));
+	}
+
+	#[test]
+	fn calls_are_dispatched_at_the_threshold() {
+		let (mut runtime, multisig) = setup();
+		let call_hash = hash_of(&transfer("dave", 100));
+		let when = Timepoint { height: 0, index: 0 };
+
+		// Alice starts the operation, with a deposit.
+		assert_eq!(
+			runtime.dispatch(
+				signed("alice"),
+				as_multi(&["bob", "charlie"], None, transfer("dave", 100))
+			),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.reserved_balance(&"alice".to_string()), 10);
+		assert!(runtime.multisig.multisig(&multisig, call_hash).is_some());
+		assert_eq!(
+			runtime.dispatch(
+				signed("alice"),
+				as_multi(&["bob", "charlie"], Some(when), transfer("dave", 100))
+			),
+			Err("operation was already approved by the caller")
+		);
+
+		// Charlie needs the timepoint of the operation to approve it.
+		assert_eq!(
+			runtime.dispatch(
+				signed("charlie"),
+				as_multi(&["alice", "bob"], None, transfer("dave", 100))
+			),
+			Err("an existing operation needs its timepoint")
+		);
+		assert_eq!(
+			runtime.dispatch(
+				signed("charlie"),
+				as_multi(&["alice", "bob"], Some(when), transfer("dave", 100))
+			),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&multisig), 900);
+		assert_eq!(runtime.balances.balance(&"dave".to_string()), 100);
+		assert_eq!(runtime.balances.reserved_balance(&"alice".to_string()), 0);
+		assert!(runtime.multisig.multisig(&multisig, call_hash).is_none());
+		assert!(matches!(
+			runtime.multisig.events().last(),
+			Some(Event::MultisigExecuted { result: Ok(()), .. })
+		));
+	}
+
+	#[test]
+	fn approve_by_hash_then_dispatch() {
+		let (mut runtime, multisig) = setup();
+		let call_hash = hash_of(&transfer("dave", 100));
+		let when = Timepoint { height: 0, index: 0 };
+		let approve = |others: &[&str], maybe_timepoint| -> RuntimeCall {
+			super::Call::approve_as_multi {
+				threshold: 2,
+				other_signatories: accounts(others),
+				maybe_timepoint,
+				call_hash,
+			}
+			.into()
+		};
+
+		assert_eq!(runtime.dispatch(signed("bob"), approve(&["alice", "charlie"], None)), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("alice"), approve(&["bob", "charlie"], Some(when))),
+			Ok(())
+		);
+		assert!(matches!(runtime.multisig.events().last(), Some(Event::MultisigApproval { .. })));
+		assert_eq!(runtime.balances.balance(&"dave".to_string()), 0);
+
+		// Anyone who approved can then dispatch the call.
+		assert_eq!(
+			runtime.dispatch(
+				signed("alice"),
+				as_multi(&["bob", "charlie"], Some(when), transfer("dave", 100))
+			),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&"dave".to_string()), 100);
+		assert_eq!(runtime.balances.balance(&multisig), 900);
+	}
+
+	#[test]
+	fn cancel_operations() {
+		let (mut runtime, multisig) = setup();
+		let call_hash = hash_of(&transfer("dave", 100));
+		let when = Timepoint { height: 0, index: 0 };
+		let cancel = |others: &[&str]| -> RuntimeCall {
+			super::Call::cancel_as_multi {
+				threshold: 2,
+				other_signatories: accounts(others),
+				timepoint: when,
+				call_hash,
+			}
+			.into()
+		};
+
+		assert_eq!(
+			runtime.dispatch(
+				signed("alice"),
+				as_multi(&["bob", "charlie"], None, transfer("dave", 100))
+			),
+			Ok(())
+		);
+		assert_eq!(
+			runtime.dispatch(signed("bob"), cancel(&["alice", "charlie"])),
+			Err("only whoever started the operation can cancel it")
+		);
+		assert_eq!(runtime.dispatch(signed("alice"), cancel(&["bob", "charlie"])), Ok(()));
+		assert_eq!(runtime.balances.reserved_balance(&"alice".to_string()), 0);
+		assert!(runtime.multisig.multisig(&multisig, call_hash).is_none());
+		assert_eq!(
+			runtime.dispatch(signed("alice"), cancel(&["bob", "charlie"])),
+			Err("operation does not exist")
+		);
+	}
+
+	#[test]
+	fn signatories_are_checked() {
+		let (mut runtime, _) = setup();
+		let threshold = |threshold, others: &[&str]| -> RuntimeCall {
+			super::Call::as_multi {
+				threshold,
+				other_signatories: accounts(others),
+				maybe_timepoint: None,
+				call: transfer("dave", 1).into(),
+			}
+			.into()
+		};
+		assert_eq!(
+			runtime.dispatch(signed("alice"), threshold(1, &["bob"])),
+			Err("threshold must be 2 or more")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), threshold(3, &["bob"])),
+			Err("threshold is higher than the number of signatories")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), threshold(2, &["bob", "alice"])),
+			Err("signatories must be unique, and not include the caller")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), threshold(2, &["bob", "charlie", "dave"])),
+			Err("too many signatories")
+		);
+	}
+}
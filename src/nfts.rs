@@ -0,0 +1,381 @@
+use core::fmt::Debug;
+use std::collections::BTreeMap;
+
+use crate::support::{ClearEvents, DispatchResult};
+
+pub trait Config: crate::system::Config {
+	/// The identifier of a collection.
+	type CollectionId: Ord + Clone + Debug;
+	/// The identifier of an item within a collection.
+	type ItemId: Ord + Clone + Debug;
+}
+
+/// What we know about a collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionDetails<AccountId> {
+	/// The account which can mint items in the collection and set their attributes.
+	pub owner: AccountId,
+	/// The number of items in the collection.
+	pub items: u32,
+}
+
+/// What we know about an item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemDetails<AccountId> {
+	pub owner: AccountId,
+	/// The account which may transfer the item on behalf of its owner, if any.
+	pub approved: Option<AccountId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	collections: BTreeMap<T::CollectionId, CollectionDetails<T::AccountId>>,
+	/// The items of each collection, keyed by `(collection, item)`.
+	#[allow(clippy::type_complexity)]
+	items: BTreeMap<(T::CollectionId, T::ItemId), ItemDetails<T::AccountId>>,
+	/// The attributes of each item, keyed by `(collection, item, key)`.
+	#[allow(clippy::type_complexity)]
+	attributes: BTreeMap<(T::CollectionId, T::ItemId, Vec<u8>), Vec<u8>>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { collections: BTreeMap::new(), items: BTreeMap::new(), attributes: BTreeMap::new() }
+	}
+
+	/// What we know about the collection `collection`, if it exists.
+	pub fn collection(
+		&self,
+		collection: &T::CollectionId,
+	) -> Option<&CollectionDetails<T::AccountId>> {
+		self.collections.get(collection)
+	}
+
+	/// What we know about the item `item` of `collection`, if it exists.
+	pub fn item(
+		&self,
+		collection: &T::CollectionId,
+		item: &T::ItemId,
+	) -> Option<&ItemDetails<T::AccountId>> {
+		self.items.get(&(collection.clone(), item.clone()))
+	}
+
+	/// The owner of the item `item` of `collection`, if it exists.
+	pub fn owner(&self, collection: &T::CollectionId, item: &T::ItemId) -> Option<&T::AccountId> {
+		self.item(collection, item).map(|item| &item.owner)
+	}
+
+	/// The value of the attribute `key` of the item `item` of `collection`, if it is set.
+	pub fn attribute(
+		&self,
+		collection: &T::CollectionId,
+		item: &T::ItemId,
+		key: &[u8],
+	) -> Option<&Vec<u8>> {
+		self.attributes.get(&(collection.clone(), item.clone(), key.to_vec()))
+	}
+
+	fn ensure_collection_owner(
+		&self,
+		collection: &T::CollectionId,
+		who: &T::AccountId,
+	) -> DispatchResult {
+		let details = self.collections.get(collection).ok_or("unknown collection")?;
+		if details.owner != *who {
+			return Err("sender is not the collection owner");
+		}
+		Ok(())
+	}
+
+	fn item_mut(
+		&mut self,
+		collection: &T::CollectionId,
+		item: &T::ItemId,
+	) -> Result<&mut ItemDetails<T::AccountId>, &'static str> {
+		self.items.get_mut(&(collection.clone(), item.clone())).ok_or("unknown item")
+	}
+}
+
+// The nfts pallet deposits no events.
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {}
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Create the collection `collection`, owned by the caller.
+	pub fn create(&mut self, caller: T::AccountId, collection: T::CollectionId) -> DispatchResult {
+		if self.collections.contains_key(&collection) {
+			return Err("collection already exists");
+		}
+		self.collections
+			.insert(collection, CollectionDetails { owner: caller, items: 0 });
+		Ok(())
+	}
+
+	/// Hand the collection `collection` over to `new_owner`. Collection owner only.
+	pub fn transfer_ownership(
+		&mut self,
+		caller: T::AccountId,
+		collection: T::CollectionId,
+		new_owner: T::AccountId,
+	) -> DispatchResult {
+		self.ensure_collection_owner(&collection, &caller)?;
+		if let Some(details) = self.collections.get_mut(&collection) {
+			details.owner = new_owner;
+		}
+		Ok(())
+	}
+
+	/// Destroy the collection `collection`, which must have no items left. Collection owner only.
+	pub fn destroy(&mut self, caller: T::AccountId, collection: T::CollectionId) -> DispatchResult {
+		self.ensure_collection_owner(&collection, &caller)?;
+		if self.collections.get(&collection).is_some_and(|details| details.items > 0) {
+			return Err("collection still has items");
+		}
+		self.collections.remove(&collection);
+		Ok(())
+	}
+
+	/// Create the item `item` in `collection`, owned by `owner`. Collection owner only.
+	pub fn mint(
+		&mut self,
+		caller: T::AccountId,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		owner: T::AccountId,
+	) -> DispatchResult {
+		self.ensure_collection_owner(&collection, &caller)?;
+		let key = (collection.clone(), item);
+		if self.items.contains_key(&key) {
+			return Err("item already exists");
+		}
+
+		let details = self.collections.get_mut(&collection).ok_or("unknown collection")?;
+		details.items = details.items.checked_add(1).ok_or("Overflow")?;
+		self.items.insert(key, ItemDetails { owner, approved: None });
+		Ok(())
+	}
+
+	/// Destroy the item `item` of `collection`, along with its attributes. Item or collection
+	/// owner only.
+	pub fn burn(
+		&mut self,
+		caller: T::AccountId,
+		collection: T::CollectionId,
+		item: T::ItemId,
+	) -> DispatchResult {
+		let owner = self.owner(&collection, &item).ok_or("unknown item")?;
+		if *owner != caller {
+			self.ensure_collection_owner(&collection, &caller)
+				.map_err(|_| "sender is not the item or collection owner")?;
+		}
+
+		self.items.remove(&(collection.clone(), item.clone()));
+		self.attributes.retain(|(c, i, _), _| *c != collection || *i != item);
+		if let Some(details) = self.collections.get_mut(&collection) {
+			details.items = details.items.saturating_sub(1);
+		}
+		Ok(())
+	}
+
+	/// Transfer the item `item` of `collection` to `dest`. Item owner or approved account only.
+	///
+	/// This clears any approval on the item.
+	pub fn transfer(
+		&mut self,
+		caller: T::AccountId,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		dest: T::AccountId,
+	) -> DispatchResult {
+		let details = self.item_mut(&collection, &item)?;
+		if details.owner != caller && details.approved.as_ref() != Some(&caller) {
+			return Err("sender is not the item owner or approved");
+		}
+		details.owner = dest;
+		details.approved = None;
+		Ok(())
+	}
+
+	/// Allow `delegate` to transfer the item `item` of `collection`, replacing any previous
+	/// approval. Item owner only.
+	pub fn approve_transfer(
+		&mut self,
+		caller: T::AccountId,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		delegate: T::AccountId,
+	) -> DispatchResult {
+		let details = self.item_mut(&collection, &item)?;
+		if details.owner != caller {
+			return Err("sender is not the item owner");
+		}
+		details.approved = Some(delegate);
+		Ok(())
+	}
+
+	/// Remove the approval on the item `item` of `collection`. Item owner only.
+	pub fn cancel_approval(
+		&mut self,
+		caller: T::AccountId,
+		collection: T::CollectionId,
+		item: T::ItemId,
+	) -> DispatchResult {
+		let details = self.item_mut(&collection, &item)?;
+		if details.owner != caller {
+			return Err("sender is not the item owner");
+		}
+		if details.approved.take().is_none() {
+			return Err("item has no approval");
+		}
+		Ok(())
+	}
+
+	/// Set the attribute `key` of the item `item` of `collection` to `value`. Collection owner
+	/// only.
+	pub fn set_attribute(
+		&mut self,
+		caller: T::AccountId,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		key: Vec<u8>,
+		value: Vec<u8>,
+	) -> DispatchResult {
+		self.ensure_collection_owner(&collection, &caller)?;
+		if self.item(&collection, &item).is_none() {
+			return Err("unknown item");
+		}
+		self.attributes.insert((collection, item, key), value);
+		Ok(())
+	}
+
+	/// Remove the attribute `key` of the item `item` of `collection`. Collection owner only.
+	pub fn clear_attribute(
+		&mut self,
+		caller: T::AccountId,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		key: Vec<u8>,
+	) -> DispatchResult {
+		self.ensure_collection_owner(&collection, &caller)?;
+		self.attributes.remove(&(collection, item, key)).ok_or("attribute is not set")?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = crate::system::RawOrigin<String>;
+		type RuntimeCall = ();
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl super::Config for TestConfig {
+		type CollectionId = u32;
+		type ItemId = u32;
+	}
+
+	fn alice() -> String {
+		"alice".to_string()
+	}
+
+	fn bob() -> String {
+		"bob".to_string()
+	}
+
+	fn charlie() -> String {
+		"charlie".to_string()
+	}
+
+	// Alice owns collection 1, in which bob owns item 1.
+	fn pallet() -> super::Pallet<TestConfig> {
+		let mut nfts = super::Pallet::<TestConfig>::new();
+		assert_eq!(nfts.create(alice(), 1), Ok(()));
+		assert_eq!(nfts.mint(alice(), 1, 1, bob()), Ok(()));
+		nfts
+	}
+
+	#[test]
+	fn collections_and_minting() {
+		let mut nfts = pallet();
+		assert_eq!(nfts.create(bob(), 1), Err("collection already exists"));
+		assert_eq!(nfts.mint(bob(), 1, 2, bob()), Err("sender is not the collection owner"));
+		assert_eq!(nfts.mint(alice(), 2, 1, bob()), Err("unknown collection"));
+		assert_eq!(nfts.mint(alice(), 1, 1, alice()), Err("item already exists"));
+		assert_eq!(nfts.collection(&1).map(|collection| collection.items), Some(1));
+
+		// Collection ownership can be handed over.
+		assert_eq!(nfts.transfer_ownership(alice(), 1, charlie()), Ok(()));
+		assert_eq!(nfts.mint(alice(), 1, 2, alice()), Err("sender is not the collection owner"));
+		assert_eq!(nfts.mint(charlie(), 1, 2, alice()), Ok(()));
+
+		// Collections can only be destroyed once empty.
+		assert_eq!(nfts.destroy(charlie(), 1), Err("collection still has items"));
+		assert_eq!(nfts.burn(bob(), 1, 1), Ok(()));
+		assert_eq!(nfts.burn(bob(), 1, 2), Err("sender is not the item or collection owner"));
+		assert_eq!(nfts.burn(charlie(), 1, 2), Ok(()));
+		assert_eq!(nfts.destroy(charlie(), 1), Ok(()));
+		assert_eq!(nfts.collection(&1), None);
+	}
+
+	#[test]
+	fn transfers_and_approvals() {
+		let mut nfts = pallet();
+		assert_eq!(
+			nfts.transfer(alice(), 1, 1, alice()),
+			Err("sender is not the item owner or approved")
+		);
+		assert_eq!(nfts.transfer(bob(), 1, 2, alice()), Err("unknown item"));
+		assert_eq!(nfts.transfer(bob(), 1, 1, charlie()), Ok(()));
+		assert_eq!(nfts.owner(&1, &1), Some(&charlie()));
+
+		// An approved account can transfer once.
+		assert_eq!(nfts.approve_transfer(bob(), 1, 1, bob()), Err("sender is not the item owner"));
+		assert_eq!(nfts.approve_transfer(charlie(), 1, 1, alice()), Ok(()));
+		assert_eq!(nfts.transfer(alice(), 1, 1, bob()), Ok(()));
+		assert_eq!(nfts.owner(&1, &1), Some(&bob()));
+		assert_eq!(nfts.item(&1, &1).and_then(|item| item.approved.clone()), None);
+
+		// Cancelled approvals can not be used.
+		assert_eq!(nfts.approve_transfer(bob(), 1, 1, alice()), Ok(()));
+		assert_eq!(nfts.cancel_approval(bob(), 1, 1), Ok(()));
+		assert_eq!(nfts.cancel_approval(bob(), 1, 1), Err("item has no approval"));
+		assert_eq!(
+			nfts.transfer(alice(), 1, 1, alice()),
+			Err("sender is not the item owner or approved")
+		);
+	}
+
+	#[test]
+	fn attributes() {
+		let mut nfts = pallet();
+		assert_eq!(
+			nfts.set_attribute(bob(), 1, 1, b"color".to_vec(), b"red".to_vec()),
+			Err("sender is not the collection owner")
+		);
+		assert_eq!(
+			nfts.set_attribute(alice(), 1, 2, b"color".to_vec(), b"red".to_vec()),
+			Err("unknown item")
+		);
+		assert_eq!(nfts.set_attribute(alice(), 1, 1, b"color".to_vec(), b"red".to_vec()), Ok(()));
+		assert_eq!(nfts.attribute(&1, &1, b"color"), Some(&b"red".to_vec()));
+
+		assert_eq!(nfts.clear_attribute(alice(), 1, 1, b"color".to_vec()), Ok(()));
+		assert_eq!(
+			nfts.clear_attribute(alice(), 1, 1, b"color".to_vec()),
+			Err("attribute is not set")
+		);
+
+		// Burning an item removes its attributes.
+		assert_eq!(nfts.set_attribute(alice(), 1, 1, b"size".to_vec(), b"big".to_vec()), Ok(()));
+		assert_eq!(nfts.burn(bob(), 1, 1), Ok(()));
+		assert_eq!(nfts.attribute(&1, &1, b"size"), None);
+	}
+}
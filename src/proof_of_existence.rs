@@ -1,46 +1,466 @@
 use core::fmt::Debug;
-use std::collections::BTreeMap;
+use num::traits::CheckedAdd;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
+    path::Path,
+};
 
-use crate::support::DispatchResult;
+use crate::support::{
+    BalanceStatus, ClearEvents, Currency, DispatchResult, Hasher, Hooks, ReservableCurrency,
+};
 
+/// The balance type of the currency used for claim deposits.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as crate::system::Config>::AccountId>>::Balance;
+
+/// The hash claims are keyed by.
+pub type HashOf<T> = <<T as Config>::Hasher as Hasher>::Output;
 
 pub trait Config: crate::system::Config {
-    type Content: Debug + Ord;
+    /// The content which is claimed. Only its hash is stored.
+    type Content: AsRef<[u8]> + Debug + Clone;
+    /// How content is hashed.
+    type Hasher: Hasher;
+    /// The currency claim deposits are reserved in, usually the `balances` pallet.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// The deposit reserved from the owner of a claim, and returned when it is revoked.
+    const CLAIM_DEPOSIT: BalanceOf<Self>;
+    /// How many blocks claims last before they expire, or `None` for claims to last forever.
+    /// Claims can be renewed for that long again before they expire.
+    const CLAIM_DURATION: Option<Self::BlockNumber>;
+    /// The maximum number of expired claims cleared at the end of each block. Any more are left
+    /// for the following blocks, or for anyone to clear with `clear_expired_claim`.
+    const MAX_EXPIRED_PER_BLOCK: u32;
+    /// The maximum number of claims an account can own.
+    const MAX_CLAIMS_PER_ACCOUNT: u32;
+}
+
+/// The errors of the proof of existence pallet. They turn into the `&'static str` of a
+/// `DispatchResult`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The content is already claimed.
+    AlreadyClaimed,
+    /// There is no claim on the content.
+    NoSuchClaim,
+    /// The claim is owned by someone other than the sender.
+    NotClaimOwner,
+    /// The claim was not offered to anyone.
+    NotOffered,
+    /// The claim was not offered to the sender.
+    NotOfferedToSender,
+    /// Claims last forever, so they can not be renewed.
+    ClaimsDoNotExpire,
+    /// The claim has not expired yet.
+    NotExpired,
+    /// The account would own more than `MAX_CLAIMS_PER_ACCOUNT` claims.
+    TooManyClaims,
+}
+
+impl From<Error> for &'static str {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::AlreadyClaimed => "this content is already claimed",
+            Error::NoSuchClaim => "claim does not exist",
+            Error::NotClaimOwner => "this content is owned by someone else",
+            Error::NotOffered => "claim was not offered",
+            Error::NotOfferedToSender => "claim was not offered to the sender",
+            Error::ClaimsDoNotExpire => "claims do not expire",
+            Error::NotExpired => "claim has not expired",
+            Error::TooManyClaims => "account owns too many claims",
+        }
+    }
+}
+
+/// The events emitted by the proof of existence pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+    /// `owner` claimed the content hashed to `hash`.
+    ClaimCreated { owner: T::AccountId, hash: HashOf<T> },
+    /// `owner` revoked its claim on `hash`.
+    ClaimRevoked { owner: T::AccountId, hash: HashOf<T> },
+    /// `from` offered its claim on `hash` to `to`, who may accept it.
+    ClaimOffered { from: T::AccountId, to: T::AccountId, hash: HashOf<T> },
+    /// The owner of the claim on `hash` withdrew its offer.
+    OfferCancelled { hash: HashOf<T> },
+    /// The claim on `hash` was handed over from `from` to `to`.
+    ClaimTransferred { from: T::AccountId, to: T::AccountId, hash: HashOf<T> },
+    /// The claim on `hash` was renewed until the block `expires`.
+    ClaimRenewed { hash: HashOf<T>, expires: T::BlockNumber },
+    /// The claim of `owner` on `hash` expired and was cleared.
+    ClaimExpired { owner: T::AccountId, hash: HashOf<T> },
 }
 
-#[derive(Debug)]
+/// A claim on some content.
+#[derive(Debug, Clone)]
+pub struct Claim<AccountId, Balance, BlockNumber> {
+    pub owner: AccountId,
+    /// The deposit reserved from the owner for the claim. It moves along with the claim.
+    pub deposit: Balance,
+    /// The block in which the claim was made.
+    pub created: BlockNumber,
+    /// The block from which the claim can be cleared by anyone, if any.
+    pub expires: Option<BlockNumber>,
+}
+
+/// The claims of a given runtime.
+pub type ClaimOf<T> = Claim<
+	<T as crate::system::Config>::AccountId,
+	BalanceOf<T>,
+	<T as crate::system::Config>::BlockNumber,
+>;
+
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
-    claims: BTreeMap<T::Content, T::AccountId>,
+    /// The claims, keyed by the hash of their content.
+    #[allow(clippy::type_complexity)]
+    claims: BTreeMap<HashOf<T>, Claim<T::AccountId, BalanceOf<T>, T::BlockNumber>>,
+    /// The account each claim was offered to, until it accepts.
+    offers: BTreeMap<HashOf<T>, T::AccountId>,
+    /// The claims of each account, so they can be listed without going through every claim.
+    owned: BTreeMap<T::AccountId, BTreeSet<HashOf<T>>>,
+    /// The claims which expire, ordered by the block they expire in.
+    expiries: BTreeSet<(T::BlockNumber, HashOf<T>)>,
+    events: Vec<Event<T>>,
 }
 
 impl<T: Config> Pallet<T> {
 
     pub fn new() -> Self {
-        Self { claims: BTreeMap::new()}
+        Self {
+            claims: BTreeMap::new(),
+            offers: BTreeMap::new(),
+            owned: BTreeMap::new(),
+            expiries: BTreeSet::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// The hash claims on `content` are keyed by.
+    pub fn hash_of(content: &[u8]) -> HashOf<T> {
+        T::Hasher::hash(content)
+    }
+
+    pub fn get_claim(&self, hash: &HashOf<T>) -> Option<&T::AccountId> {
+        self.claims.get(hash).map(|claim| &claim.owner)
+    }
+
+    /// Everything we know about the claim on `hash`.
+    pub fn claim_info(&self, hash: &HashOf<T>) -> Option<&ClaimOf<T>> {
+        self.claims.get(hash)
+    }
+
+    /// The account the claim on `hash` was offered to, if any.
+    pub fn offer(&self, hash: &HashOf<T>) -> Option<&T::AccountId> {
+        self.offers.get(hash)
+    }
+
+    /// Up to `limit` of the claims of `owner`, in order of their hash, starting after the hash
+    /// `start_after` if given. Pass the last hash of a page to get the next one.
+    pub fn claims_of(
+        &self,
+        owner: &T::AccountId,
+        start_after: Option<&HashOf<T>>,
+        limit: usize,
+    ) -> Vec<&HashOf<T>> {
+        let Some(owned) = self.owned.get(owner) else { return Vec::new() };
+        let start = match start_after {
+            Some(hash) => Bound::Excluded(hash),
+            None => Bound::Unbounded,
+        };
+        owned.range::<HashOf<T>, _>((start, Bound::Unbounded)).take(limit).collect()
+    }
+
+    /// The claim on `content`, if it was claimed.
+    pub fn verify(&self, content: &[u8]) -> Option<&ClaimOf<T>> {
+        self.claim_info(&Self::hash_of(content))
+    }
+
+    /// The claim on the content of the local file at `path`, if it was claimed.
+    pub fn verify_file(&self, path: impl AsRef<Path>) -> std::io::Result<Option<&ClaimOf<T>>> {
+        let content = std::fs::read(path)?;
+        Ok(self.verify(&content))
+    }
+
+    /// The events deposited so far.
+    pub fn events(&self) -> &[Event<T>] {
+        &self.events
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    fn ensure_owner(&self, who: &T::AccountId, hash: &HashOf<T>) -> DispatchResult {
+        let owner = self.get_claim(hash).ok_or(Error::NoSuchClaim)?;
+        if who != owner {
+            return Err(Error::NotClaimOwner.into());
+        }
+        Ok(())
+    }
+
+    // Ensure that `who` can own one more claim.
+    fn ensure_can_own(&self, who: &T::AccountId) -> DispatchResult {
+        let owned = self.owned.get(who).map_or(0, BTreeSet::len);
+        if owned >= T::MAX_CLAIMS_PER_ACCOUNT as usize {
+            return Err(Error::TooManyClaims.into());
+        }
+        Ok(())
+    }
+
+    fn remove_owned(&mut self, owner: &T::AccountId, hash: &HashOf<T>) {
+        if let Some(owned) = self.owned.get_mut(owner) {
+            owned.remove(hash);
+            if owned.is_empty() {
+                self.owned.remove(owner);
+            }
+        }
+    }
+
+    // The block in which a claim made or renewed in the block `now` expires, if any. A claim which
+    // would expire past the last block number never expires.
+    fn expiry_from(now: T::BlockNumber) -> Option<T::BlockNumber> {
+        T::CLAIM_DURATION.and_then(|duration| now.checked_add(&duration))
+    }
+
+    // Remove the claim on `hash` along with its offer and expiry, returning it.
+    fn take_claim(&mut self, hash: &HashOf<T>) -> Option<ClaimOf<T>> {
+        let claim = self.claims.remove(hash)?;
+        self.offers.remove(hash);
+        self.remove_owned(&claim.owner, hash);
+        if let Some(expires) = claim.expires {
+            self.expiries.remove(&(expires, hash.clone()));
+        }
+        Some(claim)
+    }
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+    fn clear_events(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>>
+		+ AsMut<T::Currency>
+		+ AsRef<crate::system::Pallet<T>>
+		+ AsMut<crate::system::Pallet<T>>,
+{
+    // Claim `hash` for `owner`, reserving `CLAIM_DEPOSIT` from it.
+    fn do_create_claim(runtime: &mut T, owner: T::AccountId, hash: HashOf<T>) -> DispatchResult {
+        let poe: &mut Self = runtime.as_mut();
+        if poe.claims.contains_key(&hash) {
+            return Err(Error::AlreadyClaimed.into());
+        }
+        poe.ensure_can_own(&owner)?;
+
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.reserve(&owner, T::CLAIM_DEPOSIT)?;
+
+        let system: &crate::system::Pallet<T> = runtime.as_ref();
+        let created = system.block_number();
+        let expires = Self::expiry_from(created);
+        let poe: &mut Self = runtime.as_mut();
+        let info = Claim { owner: owner.clone(), deposit: T::CLAIM_DEPOSIT, created, expires };
+        poe.claims.insert(hash.clone(), info);
+        poe.owned.entry(owner.clone()).or_default().insert(hash.clone());
+        if let Some(expires) = expires {
+            poe.expiries.insert((expires, hash.clone()));
+        }
+        poe.deposit_event(Event::ClaimCreated { owner, hash });
+        Ok(())
+    }
+
+    // Hand the claim on `hash` over from `from` to `to`, along with its deposit.
+    fn do_transfer_claim(
+        runtime: &mut T,
+        from: T::AccountId,
+        to: T::AccountId,
+        hash: HashOf<T>,
+    ) -> DispatchResult {
+        let poe: &mut Self = runtime.as_mut();
+        let deposit = poe.claims.get(&hash).ok_or(Error::NoSuchClaim)?.deposit;
+        if from != to {
+            poe.ensure_can_own(&to)?;
+        }
+
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.repatriate_reserved(&from, &to, deposit, BalanceStatus::Reserved)?;
+        if !currency.account_exists(&from) {
+            let system: &mut crate::system::Pallet<T> = runtime.as_mut();
+            system.kill_account(&from);
+        }
+
+        let poe: &mut Self = runtime.as_mut();
+        poe.offers.remove(&hash);
+        if let Some(info) = poe.claims.get_mut(&hash) {
+            info.owner = to.clone();
+        }
+        poe.remove_owned(&from, &hash);
+        poe.owned.entry(to.clone()).or_default().insert(hash.clone());
+        poe.deposit_event(Event::ClaimTransferred { from, to, hash });
+        Ok(())
+    }
+
+    // Clear the expired claim on `hash`, returning its deposit to its owner.
+    fn do_expire_claim(runtime: &mut T, hash: HashOf<T>) {
+        let poe: &mut Self = runtime.as_mut();
+        let Some(claim) = poe.take_claim(&hash) else { return };
+        poe.deposit_event(Event::ClaimExpired { owner: claim.owner.clone(), hash });
+
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.unreserve(&claim.owner, claim.deposit);
     }
+}
 
-    pub fn get_claim(&self, claim: &T::Content) -> Option<&T::AccountId> {
-        self.claims.get(claim)
+impl<T: Config> Hooks<T> for Pallet<T>
+where
+	T: AsMut<Pallet<T>>
+		+ AsMut<T::Currency>
+		+ AsRef<crate::system::Pallet<T>>
+		+ AsMut<crate::system::Pallet<T>>,
+{
+    // Clear up to `MAX_EXPIRED_PER_BLOCK` of the claims which expired by now, oldest first.
+    fn on_finalize(runtime: &mut T) {
+        let system: &crate::system::Pallet<T> = runtime.as_ref();
+        let now = system.block_number();
+        let poe: &mut Self = runtime.as_mut();
+        let expired = poe
+            .expiries
+            .iter()
+            .take_while(|(expires, _)| *expires <= now)
+            .take(T::MAX_EXPIRED_PER_BLOCK as usize)
+            .map(|(_, hash)| hash.clone())
+            .collect::<Vec<_>>();
+        for hash in expired {
+            Self::do_expire_claim(runtime, hash);
+        }
     }
 }
 
+// Claims reach the currency through `T::Currency` to handle deposits, and the system pallet to
+// learn the current block and to reap the accounts their deposits leave empty.
 #[macros::call]
-impl<T: Config> Pallet<T> {
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>>
+		+ AsMut<T::Currency>
+		+ AsRef<crate::system::Pallet<T>>
+		+ AsMut<crate::system::Pallet<T>>,
+{
 
-    pub fn create_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-		if self.claims.contains_key(&claim) {
-			return Err("this content is already claimed");
-		}
-		self.claims.insert(claim, caller);
-		Ok(())
+    /// Claim `claim` for the caller, reserving `CLAIM_DEPOSIT` from it. Only the hash of `claim`
+    /// is stored.
+    pub fn create_claim(
+		runtime: &mut T,
+		caller: T::AccountId,
+		claim: T::Content,
+	) -> DispatchResult {
+		Self::do_create_claim(runtime, caller, Self::hash_of(claim.as_ref()))
 	}
 
-    pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-        let owner = self.get_claim(&claim).ok_or("claim does not exist")?;
-        if caller != *owner {
-            return Err("this content is owned by someone else");
+    /// Claim the content hashed to `hash` for the caller, reserving `CLAIM_DEPOSIT` from it. This
+    /// keeps the content itself off chain.
+    pub fn create_claim_from_hash(
+        runtime: &mut T,
+        caller: T::AccountId,
+        hash: HashOf<T>,
+    ) -> DispatchResult {
+        Self::do_create_claim(runtime, caller, hash)
+    }
+
+    /// Revoke a claim of the caller, returning its deposit.
+    pub fn revoke_claim(runtime: &mut T, caller: T::AccountId, hash: HashOf<T>) -> DispatchResult {
+        let poe: &mut Self = runtime.as_mut();
+        poe.ensure_owner(&caller, &hash)?;
+        let deposit = poe.take_claim(&hash).map(|claim| claim.deposit);
+        poe.deposit_event(Event::ClaimRevoked { owner: caller.clone(), hash });
+
+        let currency: &mut T::Currency = runtime.as_mut();
+        if let Some(deposit) = deposit {
+            currency.unreserve(&caller, deposit);
+        }
+        Ok(())
+    }
+
+    /// Hand a claim of the caller over to `new_owner`. The deposit of the claim moves with it.
+    pub fn transfer_claim(
+        runtime: &mut T,
+        caller: T::AccountId,
+        hash: HashOf<T>,
+        new_owner: T::AccountId,
+    ) -> DispatchResult {
+        let poe: &mut Self = runtime.as_mut();
+        poe.ensure_owner(&caller, &hash)?;
+        Self::do_transfer_claim(runtime, caller, new_owner, hash)
+    }
+
+    /// Offer a claim of the caller to `new_owner`, who can take it over with `accept_claim`. This
+    /// replaces any previous offer.
+    pub fn offer_claim(
+        &mut self,
+        caller: T::AccountId,
+        hash: HashOf<T>,
+        new_owner: T::AccountId,
+    ) -> DispatchResult {
+        self.ensure_owner(&caller, &hash)?;
+        self.offers.insert(hash.clone(), new_owner.clone());
+        self.deposit_event(Event::ClaimOffered { from: caller, to: new_owner, hash });
+        Ok(())
+    }
+
+    /// Withdraw the offer of a claim of the caller.
+    pub fn cancel_offer(&mut self, caller: T::AccountId, hash: HashOf<T>) -> DispatchResult {
+        self.ensure_owner(&caller, &hash)?;
+        self.offers.remove(&hash).ok_or(Error::NotOffered)?;
+        self.deposit_event(Event::OfferCancelled { hash });
+        Ok(())
+    }
+
+    /// Take over a claim which was offered to the caller.
+    pub fn accept_claim(runtime: &mut T, caller: T::AccountId, hash: HashOf<T>) -> DispatchResult {
+        let poe: &mut Self = runtime.as_mut();
+        if poe.offer(&hash) != Some(&caller) {
+            return Err(Error::NotOfferedToSender.into());
+        }
+        let owner = poe.get_claim(&hash).ok_or(Error::NoSuchClaim)?.clone();
+        Self::do_transfer_claim(runtime, owner, caller, hash)
+    }
+
+    /// Extend a claim of the caller to last `CLAIM_DURATION` from now. Claims which have already
+    /// expired can still be renewed until they are cleared.
+    pub fn renew_claim(runtime: &mut T, caller: T::AccountId, hash: HashOf<T>) -> DispatchResult {
+        let system: &crate::system::Pallet<T> = runtime.as_ref();
+        let expires = Self::expiry_from(system.block_number()).ok_or(Error::ClaimsDoNotExpire)?;
+        let poe: &mut Self = runtime.as_mut();
+        poe.ensure_owner(&caller, &hash)?;
+
+        let old = poe.claims.get_mut(&hash).and_then(|claim| claim.expires.replace(expires));
+        if let Some(old) = old {
+            poe.expiries.remove(&(old, hash.clone()));
         }
-        self.claims.remove(&claim);
+        poe.expiries.insert((expires, hash.clone()));
+        poe.deposit_event(Event::ClaimRenewed { hash, expires });
+        Ok(())
+    }
+
+    /// Clear a claim which has expired, returning its deposit to its owner. Anyone can do this.
+    pub fn clear_expired_claim(
+        runtime: &mut T,
+        _caller: T::AccountId,
+        hash: HashOf<T>,
+    ) -> DispatchResult {
+        let system: &crate::system::Pallet<T> = runtime.as_ref();
+        let now = system.block_number();
+        let poe: &mut Self = runtime.as_mut();
+        let claim = poe.claim_info(&hash).ok_or(Error::NoSuchClaim)?;
+        if claim.expires.is_none_or(|expires| expires > now) {
+            return Err(Error::NotExpired.into());
+        }
+        Self::do_expire_claim(runtime, hash);
         Ok(())
     }
 
@@ -50,29 +470,353 @@ impl<T: Config> Pallet<T> {
 
 #[cfg(test)]
 mod test {
-	struct TestConfig;
+	use crate::{
+		balances, proof_of_existence,
+		support::{Dispatch, StdHasher},
+		system,
+	};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		#[hooks]
+		proof_of_existence: proof_of_existence::Pallet<Self>,
+	}
 
-	impl super::Config for TestConfig {
+	impl super::Config for TestRuntime {
 		type Content = String;
+		type Hasher = StdHasher;
+		type Currency = balances::Pallet<Self>;
+		const CLAIM_DEPOSIT: u128 = 10;
+		const CLAIM_DURATION: Option<u32> = Some(5);
+		const MAX_EXPIRED_PER_BLOCK: u32 = 1;
+		const MAX_CLAIMS_PER_ACCOUNT: u32 = 3;
 	}
 
-	impl crate::system::Config for TestConfig {
+	impl system::Config for TestRuntime {
 		type AccountId = String;
 		type BlockNumber = u32;
 		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	fn create_claim(claim: &str) -> RuntimeCall {
+		super::Call::create_claim { claim: claim.to_string() }.into()
+	}
+
+	fn revoke_claim(claim: &str) -> RuntimeCall {
+		super::Call::revoke_claim { hash: hash(claim) }.into()
 	}
 
+	fn hash(claim: &str) -> u64 {
+		super::Pallet::<TestRuntime>::hash_of(claim.as_bytes())
+	}
+
+	crate::support::test_helpers!();
+
 	#[test]
 	fn basic_proof_of_existence() {
-		let mut poe = super::Pallet::<TestConfig>::new();
-		assert_eq!(poe.get_claim(&"Hello, world!".to_string()), None);
-		assert_eq!(poe.create_claim("alice".to_string(), "Hello, world!".to_string()), Ok(()));
-		assert_eq!(poe.get_claim(&"Hello, world!".to_string()), Some(&"alice".to_string()));
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime.balances.set_balance(&"bob".to_string(), 100);
+		let claim = hash("Hello, world!");
+
+		assert_eq!(runtime.proof_of_existence.get_claim(&claim), None);
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("Hello, world!")), Ok(()));
+		assert_eq!(runtime.proof_of_existence.get_claim(&claim), Some(&"alice".to_string()));
 		assert_eq!(
-			poe.create_claim("bob".to_string(), "Hello, world!".to_string()),
+			runtime.dispatch(signed("bob"), create_claim("Hello, world!")),
 			Err("this content is already claimed")
 		);
-		assert_eq!(poe.revoke_claim("alice".to_string(), "Hello, world!".to_string()), Ok(()));
-		assert_eq!(poe.create_claim("bob".to_string(), "Hello, world!".to_string()), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("bob"), revoke_claim("Hello, world!")),
+			Err("this content is owned by someone else")
+		);
+		assert_eq!(runtime.dispatch(signed("alice"), revoke_claim("Hello, world!")), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("alice"), revoke_claim("Hello, world!")),
+			Err("claim does not exist")
+		);
+		assert_eq!(runtime.dispatch(signed("bob"), create_claim("Hello, world!")), Ok(()));
+	}
+
+	#[test]
+	fn claims_reserve_a_deposit() {
+		let mut runtime = TestRuntime::new();
+		let alice = "alice".to_string();
+		runtime.balances.set_balance(&alice, 15);
+
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("first")), Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 5);
+		assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+
+		// The deposit can not be spent, so a second claim is not affordable.
+		assert_eq!(
+			runtime.dispatch(signed("alice"), create_claim("second")),
+			Err("Not enough funds.")
+		);
+		assert_eq!(runtime.proof_of_existence.get_claim(&hash("second")), None);
+
+		// Revoking the claim returns the deposit.
+		assert_eq!(runtime.dispatch(signed("alice"), revoke_claim("first")), Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 15);
+		assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+	}
+
+	#[test]
+	fn claims_record_their_block() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime.system.inc_block_number();
+		runtime.system.inc_block_number();
+
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("Hello, world!")), Ok(()));
+		let info = runtime.proof_of_existence.claim_info(&hash("Hello, world!"));
+		assert!(matches!(
+			info,
+			Some(super::Claim { owner, deposit: 10, created: 2, expires: Some(7) })
+				if owner == "alice"
+		));
+	}
+
+	#[test]
+	fn transfer_claims() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime.balances.set_balance(&"bob".to_string(), 100);
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("doc")), Ok(()));
+
+		let transfer = |to: &str| {
+			RuntimeCall::from(super::Call::transfer_claim {
+				hash: hash("doc"),
+				new_owner: to.to_string(),
+			})
+		};
+		assert_eq!(
+			runtime.dispatch(signed("bob"), transfer("bob")),
+			Err("this content is owned by someone else")
+		);
+		assert_eq!(runtime.dispatch(signed("alice"), transfer("bob")), Ok(()));
+		let owner = runtime.proof_of_existence.get_claim(&hash("doc"));
+		assert_eq!(owner, Some(&"bob".to_string()));
+
+		// The deposit moved with the claim, and goes back to the new owner.
+		assert_eq!(runtime.balances.total_balance(&"alice".to_string()), 90);
+		assert_eq!(runtime.balances.reserved_balance(&"bob".to_string()), 10);
+		assert_eq!(runtime.dispatch(signed("bob"), revoke_claim("doc")), Ok(()));
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 110);
+
+		assert!(matches!(
+			runtime.proof_of_existence.events(),
+			[
+				super::Event::ClaimCreated { .. },
+				super::Event::ClaimTransferred { from, to, .. },
+				super::Event::ClaimRevoked { owner, .. },
+			] if from == "alice" && to == "bob" && owner == "bob"
+		));
+	}
+
+	#[test]
+	fn transferring_the_last_deposit_reaps_the_owner() {
+		let mut runtime = TestRuntime::new();
+		let alice = "alice".to_string();
+		runtime.balances.set_balance(&alice, 10);
+		runtime.balances.set_balance(&"bob".to_string(), 100);
+		runtime.system.inc_nonce(&alice);
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("doc")), Ok(()));
+
+		let transfer = super::Call::transfer_claim { hash: hash("doc"), new_owner: "bob".into() };
+		assert_eq!(runtime.dispatch(signed("alice"), transfer.into()), Ok(()));
+		assert_eq!(runtime.balances.total_balance(&alice), 0);
+		assert_eq!(runtime.system.nonce(&alice), 0);
+	}
+
+	#[test]
+	fn offer_and_accept_claims() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime.balances.set_balance(&"bob".to_string(), 100);
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("doc")), Ok(()));
+
+		let offer = |to: &str| {
+			RuntimeCall::from(super::Call::offer_claim {
+				hash: hash("doc"),
+				new_owner: to.to_string(),
+			})
+		};
+		let accept = || RuntimeCall::from(super::Call::accept_claim { hash: hash("doc") });
+		let cancel = || RuntimeCall::from(super::Call::cancel_offer { hash: hash("doc") });
+
+		assert_eq!(
+			runtime.dispatch(signed("bob"), accept()),
+			Err("claim was not offered to the sender")
+		);
+		assert_eq!(runtime.dispatch(signed("alice"), offer("charlie")), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("bob"), accept()),
+			Err("claim was not offered to the sender")
+		);
+
+		// A cancelled offer can not be accepted.
+		assert_eq!(runtime.dispatch(signed("alice"), cancel()), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), cancel()), Err("claim was not offered"));
+		assert_eq!(runtime.dispatch(signed("alice"), offer("bob")), Ok(()));
+		assert_eq!(runtime.dispatch(signed("bob"), accept()), Ok(()));
+		let owner = runtime.proof_of_existence.get_claim(&hash("doc"));
+		assert_eq!(owner, Some(&"bob".to_string()));
+		assert_eq!(runtime.proof_of_existence.offer(&hash("doc")), None);
+		assert_eq!(runtime.balances.reserved_balance(&"bob".to_string()), 10);
+
+		assert!(matches!(
+			runtime.proof_of_existence.events(),
+			[
+				super::Event::ClaimCreated { .. },
+				super::Event::ClaimOffered { .. },
+				super::Event::OfferCancelled { .. },
+				super::Event::ClaimOffered { .. },
+				super::Event::ClaimTransferred { from, to, .. },
+			] if from == "alice" && to == "bob"
+		));
+	}
+
+	#[test]
+	fn claims_are_keyed_by_hash() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime.balances.set_balance(&"bob".to_string(), 100);
+
+		// Claiming the hash of some content is the same as claiming the content.
+		let from_hash = super::Call::create_claim_from_hash { hash: hash("Hello, world!") };
+		assert_eq!(runtime.dispatch(signed("alice"), from_hash.into()), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("bob"), create_claim("Hello, world!")),
+			Err("this content is already claimed")
+		);
+
+		let claim = runtime.proof_of_existence.verify(b"Hello, world!");
+		assert!(matches!(claim, Some(super::Claim { owner, .. }) if owner == "alice"));
+		assert!(runtime.proof_of_existence.verify(b"Goodbye, world!").is_none());
+	}
+
+	#[test]
+	fn verify_local_files() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("Hello, world!")), Ok(()));
+
+		let path = std::env::temp_dir().join(format!("poe-verify-{}", std::process::id()));
+		std::fs::write(&path, "Hello, world!").unwrap();
+		let claim = runtime.proof_of_existence.verify_file(&path).unwrap();
+		assert!(matches!(claim, Some(super::Claim { owner, .. }) if owner == "alice"));
+
+		std::fs::write(&path, "Goodbye, world!").unwrap();
+		assert!(runtime.proof_of_existence.verify_file(&path).unwrap().is_none());
+		std::fs::remove_file(&path).unwrap();
+
+		assert!(runtime.proof_of_existence.verify_file(&path).is_err());
+	}
+
+	#[test]
+	fn claims_expire_and_renew() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("doc")), Ok(()));
+
+		let renew = || RuntimeCall::from(super::Call::renew_claim { hash: hash("doc") });
+		let clear = || RuntimeCall::from(super::Call::clear_expired_claim { hash: hash("doc") });
+
+		// Anyone can clear the claim once it expired, but not before.
+		run_to_block(&mut runtime, 4);
+		assert_eq!(runtime.dispatch(signed("bob"), clear()), Err("claim has not expired"));
+		assert_eq!(
+			runtime.dispatch(signed("bob"), renew()),
+			Err("this content is owned by someone else")
+		);
+		assert_eq!(runtime.dispatch(signed("alice"), renew()), Ok(()));
+		let claim = runtime.proof_of_existence.claim_info(&hash("doc"));
+		assert!(matches!(claim, Some(super::Claim { created: 0, expires: Some(9), .. })));
+
+		// The renewed claim survives its old expiry, and is swept at the end of its new one.
+		run_to_block(&mut runtime, 8);
+		assert!(runtime.proof_of_existence.get_claim(&hash("doc")).is_some());
+		run_to_block(&mut runtime, 9);
+		assert_eq!(runtime.proof_of_existence.get_claim(&hash("doc")), None);
+		assert_eq!(runtime.balances.reserved_balance(&"alice".to_string()), 0);
+		assert!(matches!(
+			runtime.proof_of_existence.events().last(),
+			Some(super::Event::ClaimExpired { owner, .. }) if owner == "alice"
+		));
+	}
+
+	#[test]
+	fn expired_claims_are_swept_within_the_limit() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("first")), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("second")), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("third")), Ok(()));
+
+		// Only one claim is swept per block.
+		run_to_block(&mut runtime, 5);
+		let remaining = ["first", "second", "third"]
+			.iter()
+			.filter(|claim| runtime.proof_of_existence.get_claim(&hash(claim)).is_some())
+			.count();
+		assert_eq!(remaining, 2);
+
+		// Expired claims can also be cleared by anyone before they are swept.
+		let expired = ["first", "second", "third"]
+			.into_iter()
+			.find(|claim| runtime.proof_of_existence.get_claim(&hash(claim)).is_some())
+			.unwrap();
+		let clear = super::Call::clear_expired_claim { hash: hash(expired) };
+		assert_eq!(runtime.dispatch(signed("bob"), clear.into()), Ok(()));
+
+		run_to_block(&mut runtime, 6);
+		assert_eq!(runtime.balances.reserved_balance(&"alice".to_string()), 0);
+		assert_eq!(runtime.balances.balance(&"alice".to_string()), 100);
+	}
+
+	#[test]
+	fn claims_by_owner() {
+		let mut runtime = TestRuntime::new();
+		let alice = "alice".to_string();
+		runtime.balances.set_balance(&alice, 100);
+		runtime.balances.set_balance(&"bob".to_string(), 100);
+		for claim in ["a", "b", "c"] {
+			assert_eq!(runtime.dispatch(signed("alice"), create_claim(claim)), Ok(()));
+		}
+		assert_eq!(
+			runtime.dispatch(signed("alice"), create_claim("d")),
+			Err(super::Error::TooManyClaims.into())
+		);
+
+		// Claims are listed a page at a time.
+		let mut all = [hash("a"), hash("b"), hash("c")];
+		all.sort();
+		let poe = &runtime.proof_of_existence;
+		let first = poe.claims_of(&alice, None, 2);
+		assert_eq!(first, vec![&all[0], &all[1]]);
+		assert_eq!(poe.claims_of(&alice, first.last().copied(), 2), vec![&all[2]]);
+		assert!(poe.claims_of(&"bob".to_string(), None, 2).is_empty());
+
+		// The index follows transfers and revocations.
+		let transfer =
+			super::Call::transfer_claim { hash: hash("a"), new_owner: "bob".to_string() };
+		assert_eq!(runtime.dispatch(signed("alice"), transfer.into()), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), revoke_claim("b")), Ok(()));
+		assert_eq!(runtime.proof_of_existence.claims_of(&alice, None, 10), vec![&hash("c")]);
+		let bob = runtime.proof_of_existence.claims_of(&"bob".to_string(), None, 10);
+		assert_eq!(bob, vec![&hash("a")]);
+		assert_eq!(runtime.dispatch(signed("alice"), create_claim("d")), Ok(()));
 	}
 }
@@ -0,0 +1,663 @@
+use core::fmt::Debug;
+use num::traits::{CheckedAdd, Zero};
+use std::collections::BTreeMap;
+
+use crate::{
+	support::{
+		self, ClearEvents, Contains, Currency, DispatchResult, Dispatchable, Encode, Hasher,
+		InstanceFilter, ReservableCurrency,
+	},
+	system::RawOrigin,
+};
+
+/// The balance type of the currency used for proxy deposits.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as crate::system::Config>::AccountId>>::Balance;
+
+/// The hash calls are announced by, which is `support::hash_of` with the `Hasher` of the runtime.
+pub type CallHashOf<T> = <<T as Config>::Hasher as Hasher>::Output;
+
+/// Calls are announced by their hash, so they have to be encodable.
+///
+/// The pallet has to be part of the `BaseCallFilter` of the runtime, which is how the calls nested
+/// in the call of a proxy, e.g. in a batch, are filtered by its proxy type too.
+pub trait Config: crate::system::RuntimeCallConfig<RuntimeCall: Encode> {
+	/// How announced calls are hashed.
+	type Hasher: Hasher;
+	/// The kinds of proxies, each of which only lets some calls through, e.g. "any call" or "only
+	/// calls to `balances`".
+	type ProxyType: InstanceFilter<Self::RuntimeCall> + Clone + PartialEq + Debug;
+	/// The currency deposits for proxies are reserved in, usually the `balances` pallet.
+	type Currency: ReservableCurrency<Self::AccountId>;
+	/// The deposit reserved from an account for each of its proxies.
+	const PROXY_DEPOSIT: BalanceOf<Self>;
+	/// The maximum number of proxies of an account.
+	const MAX_PROXIES: u32;
+	/// The maximum number of announcements a proxy can have pending.
+	const MAX_PENDING: u32;
+}
+
+/// The events emitted by the proxy pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+	/// `delegate` became a proxy of `delegator`.
+	ProxyAdded {
+		delegator: T::AccountId,
+		delegate: T::AccountId,
+		proxy_type: T::ProxyType,
+		delay: T::BlockNumber,
+	},
+	/// `delegate` is no longer a proxy of `delegator`.
+	ProxyRemoved {
+		delegator: T::AccountId,
+		delegate: T::AccountId,
+		proxy_type: T::ProxyType,
+		delay: T::BlockNumber,
+	},
+	/// The proxy `delegate` announced a call for `real`.
+	Announced { real: T::AccountId, delegate: T::AccountId, call_hash: CallHashOf<T> },
+	/// A call was dispatched for `real` by one of its proxies, with the given result.
+	ProxyExecuted { real: T::AccountId, delegate: T::AccountId, result: DispatchResult },
+}
+
+/// The permission of `delegate` to make calls for an account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyDefinition<AccountId, ProxyType, BlockNumber> {
+	pub delegate: AccountId,
+	/// Which calls `delegate` may make.
+	pub proxy_type: ProxyType,
+	/// How many blocks a call has to be announced for before `delegate` can make it. With no
+	/// delay, calls are made straight away.
+	pub delay: BlockNumber,
+}
+
+/// A call a proxy announced it will make for `real`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement<AccountId, Hash, BlockNumber> {
+	pub real: AccountId,
+	pub call_hash: Hash,
+	/// The block the call was announced in.
+	pub height: BlockNumber,
+}
+
+/// The proxies of a given runtime.
+pub type ProxyDefinitionOf<T> = ProxyDefinition<
+	<T as crate::system::Config>::AccountId,
+	<T as Config>::ProxyType,
+	<T as crate::system::Config>::BlockNumber,
+>;
+
+/// The announcements of a given runtime.
+pub type AnnouncementOf<T> = Announcement<
+	<T as crate::system::Config>::AccountId,
+	CallHashOf<T>,
+	<T as crate::system::Config>::BlockNumber,
+>;
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The proxies of each account.
+	#[allow(clippy::type_complexity)]
+	proxies:
+		BTreeMap<T::AccountId, Vec<ProxyDefinition<T::AccountId, T::ProxyType, T::BlockNumber>>>,
+	/// The pending announcements of each proxy.
+	#[allow(clippy::type_complexity)]
+	announcements: BTreeMap<
+		T::AccountId,
+		Vec<Announcement<T::AccountId, <T::Hasher as Hasher>::Output, T::BlockNumber>>,
+	>,
+	/// The proxy types of the calls proxies are making right now, innermost last. Every call
+	/// dispatched meanwhile must be allowed by all of them.
+	filters: Vec<T::ProxyType>,
+	events: Vec<Event<T>>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self {
+			proxies: BTreeMap::new(),
+			announcements: BTreeMap::new(),
+			filters: Vec::new(),
+			events: Vec::new(),
+		}
+	}
+
+	/// The proxies of `who`.
+	pub fn proxies(&self, who: &T::AccountId) -> &[ProxyDefinitionOf<T>] {
+		self.proxies.get(who).map_or(&[], Vec::as_slice)
+	}
+
+	/// The pending announcements of the proxy `delegate`.
+	pub fn announcements(&self, delegate: &T::AccountId) -> &[AnnouncementOf<T>] {
+		self.announcements.get(delegate).map_or(&[], Vec::as_slice)
+	}
+
+	/// The events deposited so far.
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+
+	// Remove the first announcement of `delegate` for `real` with `call_hash` which matches
+	// `predicate`.
+	fn remove_announcement_of(
+		&mut self,
+		delegate: &T::AccountId,
+		real: &T::AccountId,
+		call_hash: CallHashOf<T>,
+		predicate: impl Fn(&AnnouncementOf<T>) -> bool,
+	) -> DispatchResult {
+		let announcements =
+			self.announcements.get_mut(delegate).ok_or("announcement does not exist")?;
+		let index = announcements
+			.iter()
+			.position(|a| a.real == *real && a.call_hash == call_hash && predicate(a))
+			.ok_or("announcement does not exist")?;
+		announcements.remove(index);
+		if announcements.is_empty() {
+			self.announcements.remove(delegate);
+		}
+		Ok(())
+	}
+
+	// Ensure that `delegate` is a proxy of `real` which may make `call`, with a delay of at most
+	// `max_delay`, if given, and return the type of such a proxy.
+	fn ensure_proxy(
+		&self,
+		real: &T::AccountId,
+		delegate: &T::AccountId,
+		call: &T::RuntimeCall,
+		max_delay: Option<T::BlockNumber>,
+	) -> Result<T::ProxyType, &'static str> {
+		let proxies = self.proxies(real).iter().filter(|proxy| proxy.delegate == *delegate);
+		let mut proxies = proxies.peekable();
+		if proxies.peek().is_none() {
+			return Err("not a proxy of the account");
+		}
+		let mut allowed = proxies.filter(|proxy| proxy.proxy_type.filter(call)).peekable();
+		if allowed.peek().is_none() {
+			return Err("call is not allowed by the proxy type");
+		}
+		allowed
+			.find(|proxy| max_delay.is_none_or(|max_delay| proxy.delay <= max_delay))
+			.map(|proxy| proxy.proxy_type.clone())
+			.ok_or("proxy has a delay, so the call must be announced first")
+	}
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+// While a proxy makes a call, the calls nested in it are only let through if its proxy type allows
+// them, on top of the call itself.
+impl<T: Config> Contains<T, T::RuntimeCall> for Pallet<T>
+where
+	T: AsRef<Pallet<T>>,
+{
+	fn contains(runtime: &T, call: &T::RuntimeCall) -> bool {
+		let pallet: &Self = runtime.as_ref();
+		pallet.filters.iter().all(|filter| filter.filter(call))
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>>,
+{
+	// Dispatch `call` as a signed call of `real`, on behalf of its proxy `delegate` of
+	// `proxy_type`, which the calls nested in it are filtered by.
+	fn do_proxy(
+		runtime: &mut T,
+		real: T::AccountId,
+		delegate: T::AccountId,
+		proxy_type: T::ProxyType,
+		call: T::RuntimeCall,
+	) -> DispatchResult {
+		AsMut::<Self>::as_mut(runtime).filters.push(proxy_type);
+		let result = call.dispatch(runtime, RawOrigin::Signed(real.clone()).into());
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.filters.pop();
+		pallet.deposit_event(Event::ProxyExecuted { real, delegate, result });
+		Ok(())
+	}
+}
+
+#[allow(clippy::boxed_local)]
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsMut<T::Currency> + AsRef<crate::system::Pallet<T>>,
+{
+	/// Make `delegate` a proxy of the caller, which may make the calls `proxy_type` allows for it,
+	/// once they were announced for `delay` blocks. This reserves `PROXY_DEPOSIT` from the caller.
+	///
+	/// The delay has to end before the last block number, counting from this block.
+	pub fn add_proxy(
+		runtime: &mut T,
+		caller: T::AccountId,
+		delegate: T::AccountId,
+		proxy_type: T::ProxyType,
+		delay: T::BlockNumber,
+	) -> DispatchResult {
+		if delegate == caller {
+			return Err("an account can not be its own proxy");
+		}
+		let system: &crate::system::Pallet<T> = runtime.as_ref();
+		if system.block_number().checked_add(&delay).is_none() {
+			return Err("delay of the proxy is too long");
+		}
+		let proxy = ProxyDefinition { delegate, proxy_type, delay };
+		let pallet: &mut Self = runtime.as_mut();
+		let proxies = pallet.proxies(&caller);
+		if proxies.contains(&proxy) {
+			return Err("proxy already exists");
+		}
+		if proxies.len() >= T::MAX_PROXIES as usize {
+			return Err("account has too many proxies");
+		}
+
+		let currency: &mut T::Currency = runtime.as_mut();
+		currency.reserve(&caller, T::PROXY_DEPOSIT)?;
+
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.proxies.entry(caller.clone()).or_default().push(proxy.clone());
+		let ProxyDefinition { delegate, proxy_type, delay } = proxy;
+		pallet.deposit_event(Event::ProxyAdded { delegator: caller, delegate, proxy_type, delay });
+		Ok(())
+	}
+
+	/// Remove a proxy of the caller added by `add_proxy`, and return its deposit.
+	pub fn remove_proxy(
+		runtime: &mut T,
+		caller: T::AccountId,
+		delegate: T::AccountId,
+		proxy_type: T::ProxyType,
+		delay: T::BlockNumber,
+	) -> DispatchResult {
+		let proxy = ProxyDefinition { delegate, proxy_type, delay };
+		let pallet: &mut Self = runtime.as_mut();
+		let proxies = pallet.proxies.get_mut(&caller).ok_or("proxy does not exist")?;
+		let index = proxies.iter().position(|p| *p == proxy).ok_or("proxy does not exist")?;
+		proxies.remove(index);
+		if proxies.is_empty() {
+			pallet.proxies.remove(&caller);
+		}
+
+		let currency: &mut T::Currency = runtime.as_mut();
+		currency.unreserve(&caller, T::PROXY_DEPOSIT);
+
+		let pallet: &mut Self = runtime.as_mut();
+		let ProxyDefinition { delegate, proxy_type, delay } = proxy;
+		pallet.deposit_event(Event::ProxyRemoved {
+			delegator: caller,
+			delegate,
+			proxy_type,
+			delay,
+		});
+		Ok(())
+	}
+
+	/// Make `call` as `real`, of which the caller is a proxy with no delay.
+	pub fn proxy(
+		runtime: &mut T,
+		caller: T::AccountId,
+		real: T::AccountId,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let pallet: &mut Self = runtime.as_mut();
+		let proxy_type =
+			pallet.ensure_proxy(&real, &caller, &call, Some(T::BlockNumber::zero()))?;
+		Self::do_proxy(runtime, real, caller, proxy_type, *call)
+	}
+
+	/// Announce that the caller, a proxy of `real`, will make the call hashed to `call_hash` for
+	/// it. The call can be made with `proxy_announced` once the delay of the proxy has passed,
+	/// which gives `real` the time to reject it, or to remove the proxy.
+	pub fn announce(
+		runtime: &mut T,
+		caller: T::AccountId,
+		real: T::AccountId,
+		call_hash: CallHashOf<T>,
+	) -> DispatchResult {
+		let system: &crate::system::Pallet<T> = runtime.as_ref();
+		let height = system.block_number();
+		let pallet: &mut Self = runtime.as_mut();
+		if !pallet.proxies(&real).iter().any(|proxy| proxy.delegate == caller) {
+			return Err("not a proxy of the account");
+		}
+		if pallet.announcements(&caller).len() >= T::MAX_PENDING as usize {
+			return Err("proxy has too many pending announcements");
+		}
+
+		let announcement =
+			Announcement { real: real.clone(), call_hash: call_hash.clone(), height };
+		pallet.announcements.entry(caller.clone()).or_default().push(announcement);
+		pallet.deposit_event(Event::Announced { real, delegate: caller, call_hash });
+		Ok(())
+	}
+
+	/// Withdraw an announcement the caller made for `real`.
+	pub fn remove_announcement(
+		runtime: &mut T,
+		caller: T::AccountId,
+		real: T::AccountId,
+		call_hash: CallHashOf<T>,
+	) -> DispatchResult {
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.remove_announcement_of(&caller, &real, call_hash, |_| true)
+	}
+
+	/// Reject an announcement the proxy `delegate` made for the caller.
+	pub fn reject_announcement(
+		runtime: &mut T,
+		caller: T::AccountId,
+		delegate: T::AccountId,
+		call_hash: CallHashOf<T>,
+	) -> DispatchResult {
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.remove_announcement_of(&delegate, &caller, call_hash, |_| true)
+	}
+
+	/// Make `call` as `real`, for its proxy `delegate`, which announced it long enough ago. Anyone
+	/// can do this.
+	pub fn proxy_announced(
+		runtime: &mut T,
+		_caller: T::AccountId,
+		delegate: T::AccountId,
+		real: T::AccountId,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let system: &crate::system::Pallet<T> = runtime.as_ref();
+		let now = system.block_number();
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.ensure_proxy(&real, &delegate, &call, None)?;
+
+		// The least delayed proxy which may make the call.
+		let proxy = pallet
+			.proxies(&real)
+			.iter()
+			.filter(|proxy| proxy.delegate == delegate && proxy.proxy_type.filter(&call))
+			.min_by_key(|proxy| proxy.delay)
+			.ok_or("not a proxy of the account")?;
+		let (delay, proxy_type) = (proxy.delay, proxy.proxy_type.clone());
+		// An announcement whose delay ends past the last block number is never due.
+		let is_due = |announcement: &AnnouncementOf<T>| {
+			announcement.height.checked_add(&delay).is_some_and(|due| due <= now)
+		};
+		let call_hash = support::hash_of::<T::Hasher, _>(&call);
+		pallet
+			.remove_announcement_of(&delegate, &real, call_hash, is_due)
+			.map_err(|_| "call was not announced, or its delay has not passed yet")?;
+		Self::do_proxy(runtime, real, delegate, proxy_type, *call)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Event;
+	use crate::{
+		balances, proxy,
+		support::{hash_of, Dispatch, Encode, InstanceFilter, StdHasher},
+		system, utility,
+	};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		utility: utility::Pallet<Self>,
+		proxy: proxy::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = proxy::Pallet<Self>;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	impl utility::Config for TestRuntime {}
+
+	#[derive(Debug, Clone, PartialEq)]
+	enum ProxyType {
+		Any,
+		Balances,
+	}
+
+	impl Encode for ProxyType {
+		fn encode_to(&self, dest: &mut Vec<u8>) {
+			dest.push(self.clone() as u8);
+		}
+	}
+
+	impl InstanceFilter<RuntimeCall> for ProxyType {
+		fn filter(&self, call: &RuntimeCall) -> bool {
+			match self {
+				ProxyType::Any => true,
+				// Batches are let through, and the calls in them are filtered in turn.
+				ProxyType::Balances => {
+					matches!(call, RuntimeCall::balances(_) | RuntimeCall::utility(_))
+				},
+			}
+		}
+	}
+
+	impl super::Config for TestRuntime {
+		type ProxyType = ProxyType;
+		type Hasher = StdHasher;
+		type Currency = balances::Pallet<Self>;
+		const PROXY_DEPOSIT: u128 = 10;
+		const MAX_PROXIES: u32 = 2;
+		const MAX_PENDING: u32 = 2;
+	}
+
+	crate::support::test_helpers!();
+
+	fn add_proxy(delegate: &str, proxy_type: ProxyType, delay: u32) -> RuntimeCall {
+		super::Call::add_proxy { delegate: delegate.to_string(), proxy_type, delay }.into()
+	}
+
+	fn proxy(real: &str, call: RuntimeCall) -> RuntimeCall {
+		super::Call::proxy { real: real.to_string(), call: Box::new(call) }.into()
+	}
+
+	fn setup() -> TestRuntime {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime
+	}
+
+	#[test]
+	fn add_and_remove_proxies() {
+		let mut runtime = setup();
+		let alice = "alice".to_string();
+
+		assert_eq!(runtime.dispatch(signed("alice"), add_proxy("bob", ProxyType::Any, 0)), Ok(()));
+		assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("bob", ProxyType::Any, 0)),
+			Err("proxy already exists")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("alice", ProxyType::Any, 0)),
+			Err("an account can not be its own proxy")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("bob", ProxyType::Balances, 0)),
+			Ok(())
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("charlie", ProxyType::Any, 0)),
+			Err("account has too many proxies")
+		);
+		assert_eq!(runtime.proxy.proxies(&alice).len(), 2);
+
+		let remove = super::Call::remove_proxy {
+			delegate: "bob".to_string(),
+			proxy_type: ProxyType::Any,
+			delay: 0,
+		};
+		assert_eq!(runtime.dispatch(signed("alice"), remove.clone().into()), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), remove.into()), Err("proxy does not exist"));
+		assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+		assert!(matches!(
+			runtime.proxy.events().last(),
+			Some(Event::ProxyRemoved { proxy_type: ProxyType::Any, .. })
+		));
+	}
+
+	#[test]
+	fn proxies_make_the_calls_their_type_allows() {
+		let mut runtime = setup();
+		let alice = "alice".to_string();
+		let remark = system::Call::remark_with_event { remark: vec![] }.into();
+
+		assert_eq!(
+			runtime.dispatch(signed("bob"), proxy("alice", transfer("charlie", 20))),
+			Err("not a proxy of the account")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("bob", ProxyType::Balances, 0)),
+			Ok(())
+		);
+		assert_eq!(
+			runtime.dispatch(signed("bob"), proxy("alice", remark)),
+			Err("call is not allowed by the proxy type")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("bob"), proxy("alice", transfer("charlie", 20))),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&alice), 70);
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 20);
+		assert!(matches!(
+			runtime.proxy.events().last(),
+			Some(Event::ProxyExecuted { real, result: Ok(()), .. }) if real == "alice"
+		));
+
+		// A balances proxy can not add proxies of its own.
+		assert_eq!(
+			runtime.dispatch(signed("bob"), proxy("alice", add_proxy("bob", ProxyType::Any, 0))),
+			Err("call is not allowed by the proxy type")
+		);
+	}
+
+	#[test]
+	fn calls_nested_in_the_call_of_a_proxy_are_filtered() {
+		let mut runtime = setup();
+		let alice = "alice".to_string();
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("bob", ProxyType::Balances, 0)),
+			Ok(())
+		);
+
+		// A balances proxy can not add proxies of its own from a batch either.
+		let calls = vec![transfer("charlie", 20), add_proxy("bob", ProxyType::Any, 0)];
+		let batch = utility::Call::batch { calls }.into();
+		assert_eq!(runtime.dispatch(signed("bob"), proxy("alice", batch)), Ok(()));
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 20);
+		assert_eq!(runtime.proxy.proxies(&alice).len(), 1);
+		assert_eq!(
+			runtime.utility.events().last(),
+			Some(&utility::Event::BatchInterrupted { index: 1, error: "call is filtered" })
+		);
+
+		// The filter only lasts as long as the call of the proxy.
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("charlie", ProxyType::Any, 0)),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn delayed_proxies_announce_calls() {
+		let mut runtime = setup();
+		let alice = "alice".to_string();
+		let call_hash = hash_of::<StdHasher, _>(&transfer("charlie", 20));
+		let announce = super::Call::announce { real: alice.clone(), call_hash };
+		let proxy_announced = || -> RuntimeCall {
+			super::Call::proxy_announced {
+				delegate: "bob".to_string(),
+				real: "alice".to_string(),
+				call: transfer("charlie", 20).into(),
+			}
+			.into()
+		};
+
+		assert_eq!(runtime.dispatch(signed("alice"), add_proxy("bob", ProxyType::Any, 5)), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("bob"), proxy("alice", transfer("charlie", 20))),
+			Err("proxy has a delay, so the call must be announced first")
+		);
+		run_to_block(&mut runtime, 1);
+		assert_eq!(runtime.dispatch(signed("bob"), announce.clone().into()), Ok(()));
+		assert_eq!(runtime.proxy.announcements(&"bob".to_string()).len(), 1);
+
+		// Too early.
+		run_to_block(&mut runtime, 5);
+		assert_eq!(
+			runtime.dispatch(signed("dave"), proxy_announced()),
+			Err("call was not announced, or its delay has not passed yet")
+		);
+		run_to_block(&mut runtime, 6);
+		assert_eq!(runtime.dispatch(signed("dave"), proxy_announced()), Ok(()));
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 20);
+		assert!(runtime.proxy.announcements(&"bob".to_string()).is_empty());
+
+		// Alice can reject announcements before they are made.
+		assert_eq!(runtime.dispatch(signed("bob"), announce.into()), Ok(()));
+		let reject = super::Call::reject_announcement { delegate: "bob".to_string(), call_hash };
+		assert_eq!(runtime.dispatch(signed("alice"), reject.into()), Ok(()));
+		run_to_block(&mut runtime, 20);
+		assert_eq!(
+			runtime.dispatch(signed("dave"), proxy_announced()),
+			Err("call was not announced, or its delay has not passed yet")
+		);
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 20);
+	}
+
+	#[test]
+	fn delays_past_the_last_block_are_never_due() {
+		let mut runtime = setup();
+		let call_hash = hash_of::<StdHasher, _>(&transfer("charlie", 20));
+		let announce = super::Call::announce { real: "alice".to_string(), call_hash };
+		let proxy_announced = super::Call::proxy_announced {
+			delegate: "bob".to_string(),
+			real: "alice".to_string(),
+			call: transfer("charlie", 20).into(),
+		};
+
+		run_to_block(&mut runtime, 1);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("bob", ProxyType::Any, u32::MAX)),
+			Err("delay of the proxy is too long")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), add_proxy("bob", ProxyType::Any, u32::MAX - 1)),
+			Ok(())
+		);
+
+		// Announced a block later, the delay would end past `u32::MAX`.
+		run_to_block(&mut runtime, 2);
+		assert_eq!(runtime.dispatch(signed("bob"), announce.into()), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("dave"), proxy_announced.into()),
+			Err("call was not announced, or its delay has not passed yet")
+		);
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 0);
+	}
+}
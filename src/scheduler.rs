@@ -0,0 +1,525 @@
+use num::traits::{CheckedAdd, CheckedMul, One, Zero};
+use std::collections::BTreeMap;
+
+use crate::{
+	support::{ClearEvents, DispatchResult, Dispatchable, Hooks, Weight},
+	system::RawOrigin,
+};
+
+/// The name of a task, for scheduling it with `schedule_named` and cancelling it by name.
+pub type TaskName = Vec<u8>;
+
+/// How urgent a task is, where `0` is the most urgent. Within a block, tasks are dispatched in
+/// order of priority, and then in the order they were scheduled in.
+pub type Priority = u8;
+
+/// Where a task is in the agenda: the block it is due in, and its index there.
+pub type TaskAddress<BlockNumber> = (BlockNumber, u32);
+
+/// The block number has to be multiplied by the number of times a periodic task repeats, to check
+/// that its last run is not past the last block number.
+pub trait Config: crate::system::RuntimeCallConfig<BlockNumber: CheckedMul + From<u32>> {
+	/// The most weight the tasks of a block may use. The tasks which do not fit are retried in the
+	/// next block.
+	const MAXIMUM_WEIGHT: Weight;
+	/// The maximum number of tasks which can be scheduled for a block.
+	const MAX_SCHEDULED_PER_BLOCK: u32;
+
+	/// The weight of dispatching `call`.
+	fn call_weight(call: &Self::RuntimeCall) -> Weight;
+}
+
+/// The events emitted by the scheduler pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+	/// A task was scheduled at `task`.
+	Scheduled { task: TaskAddress<T::BlockNumber>, id: Option<TaskName> },
+	/// The task at `task` was cancelled.
+	Canceled { task: TaskAddress<T::BlockNumber>, id: Option<TaskName> },
+	/// The task at `task` was dispatched, with the given result.
+	Dispatched { task: TaskAddress<T::BlockNumber>, id: Option<TaskName>, result: DispatchResult },
+	/// A task did not fit in the weight left in its block, and was moved to `task` in the next.
+	Postponed { task: TaskAddress<T::BlockNumber>, id: Option<TaskName> },
+	/// The task at `task` weighs more than a whole block can use, so it was dropped.
+	PermanentlyOverweight { task: TaskAddress<T::BlockNumber>, id: Option<TaskName> },
+}
+
+/// A call scheduled for some block.
+#[derive(Debug, Clone)]
+pub struct Scheduled<Call, AccountId, BlockNumber> {
+	/// The name of the task, if it was scheduled with one.
+	pub id: Option<TaskName>,
+	pub priority: Priority,
+	pub call: Call,
+	/// How many blocks after this one the task runs again, and how many more times, if any.
+	pub maybe_periodic: Option<(BlockNumber, u32)>,
+	/// The origin the task was scheduled with, which the call is dispatched with.
+	pub origin: RawOrigin<AccountId>,
+}
+
+/// The tasks of a given runtime.
+pub type ScheduledOf<T> = Scheduled<
+	<T as crate::system::Config>::RuntimeCall,
+	<T as crate::system::Config>::AccountId,
+	<T as crate::system::Config>::BlockNumber,
+>;
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The tasks due in each block. Cancelled and dispatched tasks leave a `None` behind, so the
+	/// index of the other tasks does not change.
+	#[allow(clippy::type_complexity)]
+	agenda: BTreeMap<
+		T::BlockNumber,
+		Vec<Option<Scheduled<T::RuntimeCall, T::AccountId, T::BlockNumber>>>,
+	>,
+	/// Where each named task is.
+	lookup: BTreeMap<TaskName, TaskAddress<T::BlockNumber>>,
+	events: Vec<Event<T>>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { agenda: BTreeMap::new(), lookup: BTreeMap::new(), events: Vec::new() }
+	}
+
+	/// The task at `task`, if it is still scheduled.
+	pub fn task(&self, task: &TaskAddress<T::BlockNumber>) -> Option<&ScheduledOf<T>> {
+		self.agenda.get(&task.0)?.get(task.1 as usize)?.as_ref()
+	}
+
+	/// Where the task named `id` is, if it is scheduled.
+	pub fn lookup(&self, id: &[u8]) -> Option<TaskAddress<T::BlockNumber>> {
+		self.lookup.get(id).copied()
+	}
+
+	/// The events deposited so far.
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+
+	// Add `task` to the agenda of the block `when`, ignoring the limit of tasks per block.
+	fn place(&mut self, when: T::BlockNumber, task: ScheduledOf<T>) -> TaskAddress<T::BlockNumber> {
+		let agenda = self.agenda.entry(when).or_default();
+		let address = (when, agenda.len() as u32);
+		if let Some(id) = &task.id {
+			self.lookup.insert(id.clone(), address);
+		}
+		agenda.push(Some(task));
+		address
+	}
+
+	// Take the task at `address` out of the agenda, if `origin` may cancel it.
+	fn do_cancel(
+		&mut self,
+		origin: &RawOrigin<T::AccountId>,
+		address: TaskAddress<T::BlockNumber>,
+	) -> DispatchResult {
+		let slot = self
+			.agenda
+			.get_mut(&address.0)
+			.and_then(|agenda| agenda.get_mut(address.1 as usize))
+			.filter(|slot| slot.is_some())
+			.ok_or("task does not exist")?;
+		if *origin != RawOrigin::Root && slot.as_ref().map(|task| &task.origin) != Some(origin) {
+			return Err("task was scheduled by someone else");
+		}
+		let task = slot.take().ok_or("task does not exist")?;
+		if let Some(id) = &task.id {
+			self.lookup.remove(id);
+		}
+		self.deposit_event(Event::Canceled { task: address, id: task.id });
+		Ok(())
+	}
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+// Only root and signed origins can schedule tasks, to have them dispatched with the same origin.
+fn ensure_root_or_signed<T: Config>(
+	origin: T::RuntimeOrigin,
+) -> Result<RawOrigin<T::AccountId>, &'static str> {
+	match origin.into() {
+		Ok(origin @ (RawOrigin::Root | RawOrigin::Signed(_))) => Ok(origin),
+		_ => Err("bad origin: expected root or a signed account"),
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsRef<crate::system::Pallet<T>>,
+{
+	fn do_schedule(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		id: Option<TaskName>,
+		when: T::BlockNumber,
+		maybe_periodic: Option<(T::BlockNumber, u32)>,
+		priority: Priority,
+		call: T::RuntimeCall,
+	) -> DispatchResult {
+		let origin = ensure_root_or_signed::<T>(origin)?;
+		let system: &crate::system::Pallet<T> = runtime.as_ref();
+		if when <= system.block_number() {
+			return Err("task must be scheduled for a future block");
+		}
+		if maybe_periodic.is_some_and(|(period, count)| period.is_zero() || count == 0) {
+			return Err("periodic task must repeat at least once, a block or more apart");
+		}
+		let repeats_too_late = maybe_periodic.is_some_and(|(period, count)| {
+			period
+				.checked_mul(&count.into())
+				.and_then(|span| when.checked_add(&span))
+				.is_none()
+		});
+		if repeats_too_late {
+			return Err("periodic task would repeat past the last block number");
+		}
+
+		let scheduler: &mut Self = runtime.as_mut();
+		if id.as_ref().is_some_and(|id| scheduler.lookup.contains_key(id)) {
+			return Err("a task with this name is already scheduled");
+		}
+		let scheduled = scheduler.agenda.get(&when).map_or(0, Vec::len);
+		if scheduled >= T::MAX_SCHEDULED_PER_BLOCK as usize {
+			return Err("agenda of the block is full");
+		}
+
+		let task = Scheduled { id: id.clone(), priority, call, maybe_periodic, origin };
+		let address = scheduler.place(when, task);
+		scheduler.deposit_event(Event::Scheduled { task: address, id });
+		Ok(())
+	}
+}
+
+impl<T: Config> Hooks<T> for Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsRef<crate::system::Pallet<T>>,
+{
+	// Dispatch the tasks due in this block, most urgent first, as long as their weight fits.
+	fn on_initialize(runtime: &mut T) {
+		let system: &crate::system::Pallet<T> = runtime.as_ref();
+		let now = system.block_number();
+		let scheduler: &mut Self = runtime.as_mut();
+		let Some(agenda) = scheduler.agenda.remove(&now) else { return };
+
+		let mut tasks = agenda
+			.into_iter()
+			.enumerate()
+			.filter_map(|(index, task)| Some(((now, index as u32), task?)))
+			.collect::<Vec<_>>();
+		tasks.sort_by_key(|(_, task)| task.priority);
+
+		let mut used: Weight = 0;
+		for (address, task) in tasks {
+			let scheduler: &mut Self = runtime.as_mut();
+			if let Some(id) = &task.id {
+				scheduler.lookup.remove(id);
+			}
+			let weight = T::call_weight(&task.call);
+			if weight > T::MAXIMUM_WEIGHT {
+				scheduler
+					.deposit_event(Event::PermanentlyOverweight { task: address, id: task.id });
+				continue;
+			}
+			if used.saturating_add(weight) > T::MAXIMUM_WEIGHT {
+				// There is no block after the last block number to postpone the task to.
+				let Some(next) = now.checked_add(&One::one()) else { continue };
+				let address = scheduler.place(next, task.clone());
+				scheduler.deposit_event(Event::Postponed { task: address, id: task.id });
+				continue;
+			}
+			used += weight;
+
+			let result = task.call.clone().dispatch(runtime, task.origin.clone().into());
+			let scheduler: &mut Self = runtime.as_mut();
+			scheduler.deposit_event(Event::Dispatched {
+				task: address,
+				id: task.id.clone(),
+				result,
+			});
+			if let Some((period, count)) = task.maybe_periodic {
+				// A postponed task may run late enough for its next run to be past the last block
+				// number, in which case it does not repeat any more.
+				let maybe_periodic = (count > 1).then(|| (period, count - 1));
+				if let Some(when) = now.checked_add(&period) {
+					scheduler.place(when, Scheduled { maybe_periodic, ..task });
+				}
+			}
+		}
+	}
+}
+
+#[allow(clippy::boxed_local)]
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsRef<crate::system::Pallet<T>>,
+{
+	/// Dispatch `call` in the block `when`, with the same origin as this call. With
+	/// `maybe_periodic` set to `Some((period, count))`, it is dispatched `count` more times after
+	/// that, every `period` blocks. Neither may be zero.
+	pub fn schedule(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		when: T::BlockNumber,
+		maybe_periodic: Option<(T::BlockNumber, u32)>,
+		priority: Priority,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		Self::do_schedule(runtime, origin, None, when, maybe_periodic, priority, *call)
+	}
+
+	/// Cancel the task at index `index` of the block `when`. Only root and whoever scheduled it
+	/// may do this.
+	pub fn cancel(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		when: T::BlockNumber,
+		index: u32,
+	) -> DispatchResult {
+		let origin = ensure_root_or_signed::<T>(origin)?;
+		self.do_cancel(&origin, (when, index))
+	}
+
+	/// Like `schedule`, but the task is named `id`, so it can be cancelled by name.
+	pub fn schedule_named(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		id: TaskName,
+		when: T::BlockNumber,
+		maybe_periodic: Option<(T::BlockNumber, u32)>,
+		priority: Priority,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		Self::do_schedule(runtime, origin, Some(id), when, maybe_periodic, priority, *call)
+	}
+
+	/// Cancel the task named `id`. Only root and whoever scheduled it may do this.
+	pub fn cancel_named(&mut self, origin: T::RuntimeOrigin, id: TaskName) -> DispatchResult {
+		let origin = ensure_root_or_signed::<T>(origin)?;
+		let address = self.lookup(&id).ok_or("task does not exist")?;
+		self.do_cancel(&origin, address)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Event;
+	use crate::{
+		balances, scheduler,
+		support::{Dispatch, Weight},
+		system,
+	};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		#[hooks]
+		scheduler: scheduler::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	impl super::Config for TestRuntime {
+		const MAXIMUM_WEIGHT: Weight = 100;
+		const MAX_SCHEDULED_PER_BLOCK: u32 = 3;
+
+		// Remarks weigh as much as they are long, and everything else weighs 10.
+		fn call_weight(call: &RuntimeCall) -> Weight {
+			match call {
+				RuntimeCall::system(system::Call::remark { remark }) => remark.len() as Weight,
+				_ => 10,
+			}
+		}
+	}
+
+	fn remark(weight: usize) -> Box<RuntimeCall> {
+		Box::new(system::Call::remark { remark: vec![0; weight] }.into())
+	}
+
+	crate::support::test_helpers!();
+
+	#[test]
+	fn scheduled_calls_are_dispatched() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+
+		// A salary paid every 2 blocks, three times in total.
+		let salary = super::Call::schedule {
+			when: 2,
+			maybe_periodic: Some((2, 2)),
+			priority: 0,
+			call: transfer("bob", 10).into(),
+		};
+		assert_eq!(runtime.dispatch(signed("alice"), salary.clone().into()), Ok(()));
+		assert!(runtime.scheduler.task(&(2, 0)).is_some());
+
+		// Only future blocks can be scheduled for, and only by root or signed origins.
+		let now = super::Call::schedule {
+			when: 0,
+			maybe_periodic: None,
+			priority: 0,
+			call: transfer("bob", 10).into(),
+		};
+		assert_eq!(
+			runtime.dispatch(signed("alice"), now.into()),
+			Err("task must be scheduled for a future block")
+		);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::none(), salary.into()),
+			Err("bad origin: expected root or a signed account")
+		);
+
+		// Periodic tasks repeat at least once, and never within the same block.
+		for maybe_periodic in [Some((0, 2)), Some((2, 0))] {
+			let periodic = super::Call::schedule {
+				when: 2,
+				maybe_periodic,
+				priority: 0,
+				call: transfer("bob", 10).into(),
+			};
+			assert_eq!(
+				runtime.dispatch(signed("alice"), periodic.into()),
+				Err("periodic task must repeat at least once, a block or more apart")
+			);
+		}
+		for maybe_periodic in [Some((u32::MAX, 2)), Some((2, u32::MAX))] {
+			let periodic = super::Call::schedule {
+				when: 2,
+				maybe_periodic,
+				priority: 0,
+				call: transfer("bob", 10).into(),
+			};
+			assert_eq!(
+				runtime.dispatch(signed("alice"), periodic.into()),
+				Err("periodic task would repeat past the last block number")
+			);
+		}
+
+		run_to_block(&mut runtime, 1);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 0);
+		run_to_block(&mut runtime, 2);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 10);
+		assert!(runtime.scheduler.task(&(2, 0)).is_none());
+		assert!(runtime.scheduler.task(&(4, 0)).is_some());
+		assert!(matches!(
+			runtime.scheduler.events(),
+			[Event::Dispatched { task: (2, 0), result: Ok(()), .. }]
+		));
+		run_to_block(&mut runtime, 10);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 30);
+		assert_eq!(runtime.balances.balance(&"alice".to_string()), 70);
+		// Events only describe the current block, which dispatched nothing.
+		assert!(runtime.scheduler.events().is_empty());
+	}
+
+	#[test]
+	fn tasks_run_by_priority_within_the_weight() {
+		let mut runtime = TestRuntime::new();
+		let schedule = |priority, weight| super::Call::schedule {
+			when: 1,
+			maybe_periodic: None,
+			priority,
+			call: remark(weight),
+		};
+
+		// The agenda of a block is limited.
+		assert_eq!(runtime.dispatch(signed("alice"), schedule(2, 60).into()), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), schedule(1, 60).into()), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), schedule(0, 101).into()), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("alice"), schedule(0, 1).into()),
+			Err("agenda of the block is full")
+		);
+
+		// The most urgent task can never fit, and only one of the others fits.
+		run_to_block(&mut runtime, 1);
+		assert!(matches!(
+			runtime.scheduler.events(),
+			[
+				Event::PermanentlyOverweight { task: (1, 2), .. },
+				Event::Dispatched { task: (1, 1), result: Ok(()), .. },
+				Event::Postponed { task: (2, 0), .. },
+			]
+		));
+
+		// The other one is retried in the next block.
+		run_to_block(&mut runtime, 2);
+		assert!(matches!(
+			runtime.scheduler.events().last(),
+			Some(Event::Dispatched { task: (2, 0), result: Ok(()), .. })
+		));
+	}
+
+	#[test]
+	fn cancel_tasks() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		let named = super::Call::schedule_named {
+			id: b"salary".to_vec(),
+			when: 3,
+			maybe_periodic: None,
+			priority: 0,
+			call: transfer("bob", 10).into(),
+		};
+		assert_eq!(runtime.dispatch(signed("alice"), named.clone().into()), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("alice"), named.into()),
+			Err("a task with this name is already scheduled")
+		);
+		assert_eq!(runtime.scheduler.lookup(b"salary"), Some((3, 0)));
+		let unnamed = super::Call::schedule {
+			when: 3,
+			maybe_periodic: None,
+			priority: 0,
+			call: transfer("bob", 10).into(),
+		};
+		assert_eq!(runtime.dispatch(signed("alice"), unnamed.into()), Ok(()));
+
+		// Only whoever scheduled a task, or root, can cancel it.
+		let cancel_named = super::Call::cancel_named { id: b"salary".to_vec() };
+		assert_eq!(
+			runtime.dispatch(signed("bob"), cancel_named.clone().into()),
+			Err("task was scheduled by someone else")
+		);
+		assert_eq!(runtime.dispatch(signed("alice"), cancel_named.clone().into()), Ok(()));
+		assert_eq!(runtime.scheduler.lookup(b"salary"), None);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), cancel_named.into()),
+			Err("task does not exist")
+		);
+		let cancel = super::Call::cancel { when: 3, index: 1 };
+		assert_eq!(runtime.dispatch(RuntimeOrigin::root(), cancel.into()), Ok(()));
+
+		run_to_block(&mut runtime, 3);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 0);
+		assert!(!runtime
+			.scheduler
+			.events()
+			.iter()
+			.any(|event| matches!(event, Event::Dispatched { .. })));
+	}
+}
@@ -0,0 +1,279 @@
+use crate::{
+	support::{ClearEvents, DispatchResult, Dispatchable},
+	system::{ensure_signed, RawOrigin},
+};
+
+pub trait Config: crate::system::RuntimeCallConfig {}
+
+/// The events emitted by the sudo pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+	/// A call was dispatched with the root origin, with the given result.
+	Sudid { sudo_result: DispatchResult },
+	/// The sudo key was changed from `old` to `new`.
+	KeyChanged { old: Option<T::AccountId>, new: T::AccountId },
+	/// A call was dispatched on behalf of another account, with the given result.
+	SudoAsDone { sudo_result: DispatchResult },
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The account allowed to make privileged calls, if any.
+	key: Option<T::AccountId>,
+	events: Vec<Event<T>>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { key: None, events: Vec::new() }
+	}
+
+	/// Set the sudo key when the chain starts, without any checks.
+	pub fn initialize_key(&mut self, key: T::AccountId) {
+		self.key = Some(key);
+	}
+
+	/// The current sudo key, if any.
+	pub fn key(&self) -> Option<&T::AccountId> {
+		self.key.as_ref()
+	}
+
+	/// The events deposited so far.
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+
+	fn ensure_sudo(&self, who: &T::AccountId) -> DispatchResult {
+		if self.key.as_ref() != Some(who) {
+			return Err("sender must be the sudo key");
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+#[allow(clippy::boxed_local)]
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>>,
+{
+	/// Dispatch `call` with the root origin. Only the sudo key may do this.
+	///
+	/// The call itself failing does not make this fail, its result is reported in an event.
+	pub fn sudo(
+		runtime: &mut T,
+		caller: T::AccountId,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.ensure_sudo(&caller)?;
+
+		let sudo_result = call.dispatch(runtime, RawOrigin::Root.into());
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.deposit_event(Event::Sudid { sudo_result });
+		Ok(())
+	}
+
+	/// Dispatch `call` with a signed origin from `who`. Only the sudo key may do this.
+	///
+	/// The call itself failing does not make this fail, its result is reported in an event.
+	pub fn sudo_as(
+		runtime: &mut T,
+		caller: T::AccountId,
+		who: T::AccountId,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.ensure_sudo(&caller)?;
+
+		let sudo_result = call.dispatch(runtime, RawOrigin::Signed(who).into());
+		let pallet: &mut Self = runtime.as_mut();
+		pallet.deposit_event(Event::SudoAsDone { sudo_result });
+		Ok(())
+	}
+
+	/// Hand the sudo key over to `new`. Only the sudo key may do this.
+	pub fn set_key(&mut self, origin: T::RuntimeOrigin, new: T::AccountId) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.ensure_sudo(&caller)?;
+
+		let old = self.key.replace(new.clone());
+		self.deposit_event(Event::KeyChanged { old, new });
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{balances, sudo, support::Dispatch, system, utility};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		sudo: sudo::Pallet<Self>,
+		utility: utility::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	impl super::Config for TestRuntime {}
+
+	impl utility::Config for TestRuntime {}
+
+	fn set_balance(who: &str, amount: u128) -> Box<RuntimeCall> {
+		Box::new(RuntimeCall::balances(balances::Call::force_set_balance {
+			who: who.to_string(),
+			amount,
+		}))
+	}
+
+	#[test]
+	fn sudo_dispatches_as_root() {
+		let mut runtime = TestRuntime::new();
+		runtime.sudo.initialize_key("alice".to_string());
+
+		// Only the key can use sudo.
+		assert_eq!(
+			runtime.dispatch(
+				RuntimeOrigin::signed("bob".to_string()),
+				RuntimeCall::sudo(super::Call::sudo { call: set_balance("bob", 100) }),
+			),
+			Err("sender must be the sudo key")
+		);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 0);
+
+		// The key can make root calls.
+		assert_eq!(
+			runtime.dispatch(
+				RuntimeOrigin::signed("alice".to_string()),
+				RuntimeCall::sudo(super::Call::sudo { call: set_balance("bob", 100) }),
+			),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 100);
+		assert!(matches!(runtime.sudo.events(), [super::Event::Sudid { sudo_result: Ok(()) }]));
+
+		// Calls which fail are reported in the event.
+		assert_eq!(
+			runtime.dispatch(
+				RuntimeOrigin::signed("alice".to_string()),
+				RuntimeCall::sudo(super::Call::sudo {
+					call: Box::new(RuntimeCall::balances(balances::Call::transfer {
+						to: "bob".to_string(),
+						amount: 10,
+					})),
+				}),
+			),
+			Ok(())
+		);
+		assert!(matches!(
+			runtime.sudo.events().last(),
+			Some(super::Event::Sudid { sudo_result: Err("bad origin: expected a signed account") })
+		));
+	}
+
+	#[test]
+	fn sudo_as_dispatches_as_signed() {
+		let mut runtime = TestRuntime::new();
+		runtime.sudo.initialize_key("alice".to_string());
+		runtime.balances.set_balance(&"bob".to_string(), 100);
+
+		let transfer = Box::new(RuntimeCall::balances(balances::Call::transfer {
+			to: "charlie".to_string(),
+			amount: 30,
+		}));
+		assert_eq!(
+			runtime.dispatch(
+				RuntimeOrigin::signed("alice".to_string()),
+				RuntimeCall::sudo(super::Call::sudo_as { who: "bob".to_string(), call: transfer }),
+			),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 70);
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 30);
+		assert!(matches!(
+			runtime.sudo.events(),
+			[super::Event::SudoAsDone { sudo_result: Ok(()) }]
+		));
+	}
+
+	#[test]
+	fn set_key_hands_over_sudo() {
+		let mut runtime = TestRuntime::new();
+		runtime.sudo.initialize_key("alice".to_string());
+
+		let set_key = |new: &str| RuntimeCall::sudo(super::Call::set_key { new: new.to_string() });
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed("bob".to_string()), set_key("bob")),
+			Err("sender must be the sudo key")
+		);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed("alice".to_string()), set_key("bob")),
+			Ok(())
+		);
+		assert_eq!(runtime.sudo.key(), Some(&"bob".to_string()));
+		assert!(matches!(
+			runtime.sudo.events(),
+			[super::Event::KeyChanged { old: Some(old), new }] if old == "alice" && new == "bob"
+		));
+
+		// The new key can use sudo, the old one can not.
+		assert_eq!(
+			runtime.dispatch(
+				RuntimeOrigin::signed("alice".to_string()),
+				RuntimeCall::sudo(super::Call::sudo { call: set_balance("alice", 1) }),
+			),
+			Err("sender must be the sudo key")
+		);
+		assert_eq!(
+			runtime.dispatch(
+				RuntimeOrigin::signed("bob".to_string()),
+				RuntimeCall::sudo(super::Call::sudo { call: set_balance("alice", 1) }),
+			),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn sudo_nests_calls() {
+		let mut runtime = TestRuntime::new();
+		runtime.sudo.initialize_key("alice".to_string());
+
+		let calls = vec![*set_balance("bob", 10), *set_balance("charlie", 20)];
+		let batch: RuntimeCall = utility::Call::batch_all { calls }.into();
+		let sudo: RuntimeCall = super::Call::sudo { call: Box::new(batch) }.into();
+
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed("bob".to_string()), sudo.clone()),
+			Err("sender must be the sudo key")
+		);
+		assert_eq!(runtime.dispatch(RuntimeOrigin::signed("alice".to_string()), sudo), Ok(()));
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 10);
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 20);
+		assert_eq!(runtime.utility.events().last(), Some(&utility::Event::BatchCompleted));
+	}
+}
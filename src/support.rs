@@ -7,17 +7,520 @@ pub struct Header<BlockNumber> {
     pub block_number: BlockNumber,
 }
 
-pub struct Extrinsic<Caller, Call> {
-    pub caller: Caller,
-    pub call: Call,
+pub enum Extrinsic<Caller, Call> {
+    /// A call signed by `caller`.
+    Signed { caller: Caller, call: Call },
+    /// A call which is not signed, but put at the start of the block by its author, e.g. to set
+    /// the time. See `ProvideInherent`.
+    Inherent { call: Call },
+    /// A call which is not signed, e.g. because it comes from an account with no funds to pay for
+    /// it. It is only dispatched if the pallet it calls approves it, see `ValidateUnsigned`.
+    Unsigned { call: Call },
 }
 
 pub type DispatchResult = Result<(), &'static str>;
 
+/// How much of the resources of a block something uses, e.g. the calls the scheduler dispatches.
+pub type Weight = u64;
+
+/// The name of a piece of `InherentData`, e.g. `*b"timstap0"`.
+pub type InherentIdentifier = [u8; 8];
+
+/// The data the author of a block makes its inherents from, e.g. the current time. This never
+/// leaves the author, so each piece can be of whatever type the pallet using it expects.
+#[derive(Default)]
+pub struct InherentData {
+    data: std::collections::BTreeMap<InherentIdentifier, Box<dyn std::any::Any>>,
+}
+
+impl InherentData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Provide `value` as the data `id`, replacing any data it had.
+    pub fn put<V: 'static>(&mut self, id: InherentIdentifier, value: V) {
+        self.data.insert(id, Box::new(value));
+    }
+
+    /// The data `id`, if there is some of type `V`.
+    pub fn get<V: 'static>(&self, id: &InherentIdentifier) -> Option<&V> {
+        self.data.get(id)?.downcast_ref()
+    }
+}
+
+/// A pallet with inherents: calls which are not signed by anybody, but put at the start of every
+/// block by its author. The runtime calls these for the pallets marked with `#[inherent]`.
+pub trait ProvideInherent {
+    type Call;
+
+    /// The data this pallet makes its inherents from.
+    const INHERENT_IDENTIFIER: InherentIdentifier;
+
+    /// The inherent the author of a block should include, if any.
+    fn create_inherent(data: &InherentData) -> Option<Self::Call>;
+
+    /// Whether `call` is one of the inherents of this pallet. Anything else is rejected when put in
+    /// a block as an inherent.
+    fn is_inherent(call: &Self::Call) -> bool;
+
+    /// Whether every block must include an inherent of this pallet.
+    fn is_inherent_required() -> bool {
+        false
+    }
+
+    /// Check an inherent of a block made by someone else against our own data, e.g. that the
+    /// time it sets is not too far from ours.
+    fn check_inherent(_call: &Self::Call, _data: &InherentData) -> DispatchResult {
+        Ok(())
+    }
+}
+
+/// A value as bytes, e.g. to hash it. Unlike its `Debug` output, the encoding of a value only
+/// changes if the value does.
+///
+/// Integers are encoded in little endian, and sequences are prefixed with their length as a `u32`.
+/// `macros::call` implements this for each pallet's `Call`, and `macros::runtime` for the
+/// `RuntimeCall`.
+pub trait Encode {
+    /// Append the encoding of `self` to `dest`.
+    fn encode_to(&self, dest: &mut Vec<u8>);
+
+    /// The encoding of `self`.
+    fn encode(&self) -> Vec<u8> {
+        let mut dest = Vec::new();
+        self.encode_to(&mut dest);
+        dest
+    }
+}
+
+macro_rules! impl_encode_for_int {
+    ($($int:ty),*) => {
+        $(
+            impl Encode for $int {
+                fn encode_to(&self, dest: &mut Vec<u8>) {
+                    dest.extend(self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_encode_for_int!(u8, u16, u32, u64, u128);
+
+impl Encode for bool {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.push(*self as u8);
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (self.len() as u32).encode_to(dest);
+        for item in self {
+            item.encode_to(dest);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_slice().encode_to(dest);
+    }
+}
+
+impl Encode for str {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_bytes().encode_to(dest);
+    }
+}
+
+impl Encode for String {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_str().encode_to(dest);
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        match self {
+            None => dest.push(0),
+            Some(value) => {
+                dest.push(1);
+                value.encode_to(dest);
+            }
+        }
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (**self).encode_to(dest);
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Box<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (**self).encode_to(dest);
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+        self.1.encode_to(dest);
+    }
+}
+
+impl<A: Encode, B: Encode, C: Encode> Encode for (A, B, C) {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.0.encode_to(dest);
+        self.1.encode_to(dest);
+        self.2.encode_to(dest);
+    }
+}
+
+/// A way of hashing some data, e.g. content which is claimed.
+pub trait Hasher {
+    type Output: Ord + Clone + core::fmt::Debug + Encode;
+
+    fn hash(data: &[u8]) -> Self::Output;
+}
+
+/// Hashes with the default hasher of the standard library. This is fast, but not cryptographically
+/// secure, so runtimes which need to resist collisions should bring their own `Hasher`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StdHasher;
+
+impl Hasher for StdHasher {
+    type Output = u64;
+
+    fn hash(data: &[u8]) -> u64 {
+        let mut hasher = std::hash::DefaultHasher::new();
+        std::hash::Hasher::write(&mut hasher, data);
+        std::hash::Hasher::finish(&hasher)
+    }
+}
+
+/// The hash of the encoding of `value`, e.g. of a call which is approved or announced by its hash.
+pub fn hash_of<H: Hasher, V: Encode + ?Sized>(value: &V) -> H::Output {
+    H::hash(&value.encode())
+}
+
+/// An account id which can be derived from a hash, for accounts which nobody holds the key of, e.g.
+/// multisig accounts, which are derived from their signatories.
+pub trait FromHash {
+    /// The account id of the hash encoded as `hash`.
+    fn from_hash(hash: &[u8]) -> Self;
+}
+
+impl FromHash for String {
+    fn from_hash(hash: &[u8]) -> Self {
+        hash.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+/// Whether an operation on some funds may leave their owner below the existential deposit, and
+/// so get its account reaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExistenceRequirement {
+    /// The operation fails rather than reaping the account.
+    KeepAlive,
+    /// The account may be reaped.
+    AllowDeath,
+}
+
+/// Which part of an account's balance some funds are moved into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceStatus {
+    /// The funds can be spent by their owner.
+    Free,
+    /// The funds are set aside, e.g. as a deposit.
+    Reserved,
+}
+
+/// The name of a lock on some funds, e.g. `*b"vesting "`.
+pub type LockIdentifier = [u8; 8];
+
+/// The operations which some locked funds can not be withdrawn for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawReasons {
+    /// Transferring the funds away, or burning them.
+    pub transfer: bool,
+    /// Reserving the funds.
+    pub reserve: bool,
+}
+
+impl WithdrawReasons {
+    pub const TRANSFER: Self = Self { transfer: true, reserve: false };
+    pub const RESERVE: Self = Self { transfer: false, reserve: true };
+    pub const ALL: Self = Self { transfer: true, reserve: true };
+
+    /// Whether any of the reasons in `other` are also in `self`.
+    pub fn intersects(self, other: Self) -> bool {
+        (self.transfer && other.transfer) || (self.reserve && other.reserve)
+    }
+
+    /// The reasons in either `self` or `other`.
+    pub fn union(self, other: Self) -> Self {
+        Self { transfer: self.transfer || other.transfer, reserve: self.reserve || other.reserve }
+    }
+}
+
+/// Some fungible currency, e.g. `balances::Pallet`, as other pallets see it.
+///
+/// A pallet which needs one declares `type Currency: Currency<Self::AccountId>` in its `Config`,
+/// and reaches it from its calls through `AsMut<T::Currency>` on the runtime.
+pub trait Currency<AccountId> {
+    type Balance: Copy + PartialOrd + core::fmt::Debug;
+
+    /// The smallest balance an account can have.
+    fn minimum_balance() -> Self::Balance;
+
+    /// The total amount of currency in existence.
+    fn total_issuance(&self) -> Self::Balance;
+
+    /// The balance of `who` which it can spend, if nothing is locked.
+    fn free_balance(&self, who: &AccountId) -> Self::Balance;
+
+    /// The free and reserved balance of `who` together.
+    fn total_balance(&self, who: &AccountId) -> Self::Balance;
+
+    /// Whether `who` has enough funds for its account to exist. Pallets which move the funds of
+    /// `who` away, e.g. by repatriating its reserved balance, kill its system account if not.
+    fn account_exists(&self, who: &AccountId) -> bool;
+
+    /// Move `amount` from `from` to `to`.
+    fn transfer(
+        &mut self,
+        from: &AccountId,
+        to: &AccountId,
+        amount: Self::Balance,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult;
+
+    /// Create `amount` new currency in the account of `who`.
+    fn deposit(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+
+    /// Destroy `amount` of the currency of `who`.
+    fn withdraw(
+        &mut self,
+        who: &AccountId,
+        amount: Self::Balance,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult;
+}
+
+/// A currency which can set some of an account's funds aside, e.g. as a deposit.
+pub trait ReservableCurrency<AccountId>: Currency<AccountId> {
+    /// The balance of `who` which is set aside.
+    fn reserved_balance(&self, who: &AccountId) -> Self::Balance;
+
+    /// Set `amount` of the free balance of `who` aside.
+    fn reserve(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+
+    /// Make up to `amount` of the reserved balance of `who` free again, and return how much of
+    /// `amount` was not reserved.
+    fn unreserve(&mut self, who: &AccountId, amount: Self::Balance) -> Self::Balance;
+
+    /// Destroy up to `amount` of the reserved balance of `who`, and return how much of `amount`
+    /// was not reserved.
+    fn slash_reserved(&mut self, who: &AccountId, amount: Self::Balance) -> Self::Balance;
+
+    /// Move up to `amount` of the reserved balance of `slashed` to `beneficiary`, and return how
+    /// much of `amount` was not reserved.
+    fn repatriate_reserved(
+        &mut self,
+        slashed: &AccountId,
+        beneficiary: &AccountId,
+        amount: Self::Balance,
+        status: BalanceStatus,
+    ) -> Result<Self::Balance, &'static str>;
+}
+
+/// A currency which can stop some of an account's funds from being withdrawn.
+pub trait LockableCurrency<AccountId>: Currency<AccountId> {
+    /// Lock `amount` of the free balance of `who` against `reasons`, replacing the lock `id`.
+    fn set_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+    );
+
+    /// Like `set_lock`, but the lock `id` only grows.
+    fn extend_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+    );
+
+    /// Remove the lock `id` from `who`.
+    fn remove_lock(&mut self, id: LockIdentifier, who: &AccountId);
+}
+
+/// The time of the current block, as other pallets see it, e.g. `timestamp::Pallet`.
+///
+/// A pallet which needs one declares `type Time: Time` in its `Config`, and reaches it from its
+/// calls through `AsRef<T::Time>` on the runtime.
+// None of the pallets of the runtime need the time yet.
+#[allow(dead_code)]
+pub trait Time {
+    type Moment: Copy + Ord + core::fmt::Debug;
+
+    /// The time the current block was made at.
+    fn now(&self) -> Self::Moment;
+}
+
+/// A kind of permission which lets some calls through, e.g. the proxy types of a runtime, which
+/// only let a proxy make certain calls for the account it acts for.
+pub trait InstanceFilter<Call> {
+    /// Whether `call` is allowed.
+    fn filter(&self, call: &Call) -> bool;
+}
+
+/// Whether some `Call` is allowed in `Runtime` right now, e.g. the `system::Config::BaseCallFilter`
+/// of a runtime, which every call is checked against before it is dispatched.
+pub trait Contains<Runtime: ?Sized, Call> {
+    fn contains(runtime: &Runtime, call: &Call) -> bool;
+}
+
+/// The filter which allows every call.
+// Only the test runtimes allow every call.
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct Everything;
+
+impl<Runtime: ?Sized, Call> Contains<Runtime, Call> for Everything {
+    fn contains(_runtime: &Runtime, _call: &Call) -> bool {
+        true
+    }
+}
+
+/// The filter which allows the calls both `A` and `B` allow, e.g. for a runtime which pauses calls
+/// and filters the calls of proxies.
+impl<Runtime: ?Sized, Call, A, B> Contains<Runtime, Call> for (A, B)
+where
+    A: Contains<Runtime, Call>,
+    B: Contains<Runtime, Call>,
+{
+    fn contains(runtime: &Runtime, call: &Call) -> bool {
+        A::contains(runtime, call) && B::contains(runtime, call)
+    }
+}
+
+/// The name of a call within its pallet, which is the name of its function. This is implemented by
+/// `macros::call` for each pallet's `Call`.
+pub trait GetCallName {
+    fn call_name(&self) -> &'static str;
+
+    /// The names of all the calls of the pallet.
+    fn call_names() -> &'static [&'static str];
+}
+
+/// The names of a call of the runtime, e.g. `balances` and `transfer`. This is implemented by
+/// `macros::runtime` for the `RuntimeCall`.
+pub trait GetCallMetadata {
+    /// The name of the pallet of the call, as a field of the runtime.
+    fn pallet_name(&self) -> &'static str;
+
+    /// The name of the call within its pallet.
+    fn call_name(&self) -> &'static str;
+
+    /// The names of all the pallets of the runtime.
+    fn pallet_names() -> &'static [&'static str];
+
+    /// The names of all the calls of the pallet `pallet_name`, if the runtime has such a pallet.
+    fn call_names(pallet_name: &str) -> &'static [&'static str];
+}
+
 pub trait Dispatch {
-    type Caller;
+    type Origin;
 
     type Call;
  
-    fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult;
-}
\ No newline at end of file
+    fn dispatch(&mut self, origin: Self::Origin, call: Self::Call) -> DispatchResult;
+}
+
+/// A call which is dispatched with access to the whole `Runtime` it is part of, rather than only
+/// to the pallet which defines it. This is implemented by `macros::call` for each pallet's `Call`.
+pub trait Dispatchable<Runtime> {
+    type Origin;
+
+    fn dispatch(self, runtime: &mut Runtime, origin: Self::Origin) -> DispatchResult;
+}
+
+/// Logic which runs with every block, rather than in response to an extrinsic. The runtime calls
+/// these for the pallets marked with `#[hooks]`, in the order they are declared.
+pub trait Hooks<Runtime> {
+    /// Called at the start of every block, before its extrinsics.
+    fn on_initialize(_runtime: &mut Runtime) {}
+
+    /// Called at the end of every block, after its extrinsics.
+    fn on_finalize(_runtime: &mut Runtime) {}
+}
+
+/// The events of a pallet only describe the current block. The runtime clears them for every
+/// pallet when a block starts, before the hooks run, so they do not pile up forever.
+pub trait ClearEvents {
+    fn clear_events(&mut self);
+}
+
+/// A pallet which accepts some of its calls unsigned, e.g. a faucet. The runtime asks the pallets
+/// marked with `#[unsigned]`, and rejects unsigned calls to any other pallet.
+pub trait ValidateUnsigned<Runtime> {
+    type Call;
+
+    /// Check whether the unsigned `call` may be dispatched, with the none origin.
+    fn validate_unsigned(runtime: &Runtime, call: &Self::Call) -> DispatchResult;
+}
+
+/// Run `f` against `runtime`, rolling back every change it made if it fails.
+///
+/// This simply keeps a copy of the whole runtime around while `f` runs, which is fine for a state
+/// machine as small as ours.
+pub fn with_transaction<Runtime: Clone, R>(
+    runtime: &mut Runtime,
+    f: impl FnOnce(&mut Runtime) -> Result<R, &'static str>,
+) -> Result<R, &'static str> {
+    let snapshot = runtime.clone();
+    let result = f(runtime);
+    if result.is_err() {
+        *runtime = snapshot;
+    }
+    result
+}
+
+/// The helpers the tests of pallets share, for the `TestRuntime`, `RuntimeOrigin` and `RuntimeCall`
+/// of the test module this is used in, whose accounts are `String`s.
+#[cfg(test)]
+macro_rules! test_helpers {
+    () => {
+        #[allow(dead_code)]
+        fn signed(who: &str) -> RuntimeOrigin {
+            RuntimeOrigin::signed(who.to_string())
+        }
+
+        // A transfer with `balances`, whose balances are `u128`s.
+        #[allow(dead_code)]
+        fn transfer(to: &str, amount: u128) -> RuntimeCall {
+            crate::balances::Call::transfer { to: to.to_string(), amount }.into()
+        }
+
+        // Execute empty blocks until block `n`.
+        #[allow(dead_code)]
+        fn run_to_block(runtime: &mut TestRuntime, n: u32) {
+            while runtime.system.block_number() < n {
+                let block_number = runtime.system.block_number() + 1;
+                let header = crate::support::Header { block_number };
+                let block = crate::support::Block { header, extrinsics: vec![] };
+                assert_eq!(runtime.execute_block(block), Ok(()));
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+pub(crate) use test_helpers;
@@ -1,32 +1,136 @@
 use std::collections::BTreeMap;
 use core::ops::AddAssign;
-use num::traits::{ Zero, One};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use num::traits::{ CheckedAdd, Zero, One};
+
+use crate::support::{ClearEvents, Contains, Dispatchable, DispatchResult};
 
 pub trait Config {
     type AccountId: Ord + Clone;
-    type BlockNumber: Zero + One + AddAssign + Copy;
+    type BlockNumber: Zero + One + AddAssign + CheckedAdd + Copy + Ord;
     type Nonce: Zero + One + Copy;
+    /// The origin calls are dispatched with. This is generated by `macros::runtime`, and wraps a
+    /// `RawOrigin` along with any custom origins defined by pallets.
+    type RuntimeOrigin: Clone
+        + From<RawOrigin<Self::AccountId>>
+        + Into<Result<RawOrigin<Self::AccountId>, Self::RuntimeOrigin>>;
+    /// The outer call type of the runtime. This is generated by `macros::runtime`, and bounded by
+    /// `RuntimeCallConfig` for the pallets which dispatch calls.
+    type RuntimeCall;
+    /// Which calls the runtime allows right now. The runtime checks every call against it before
+    /// dispatching it, e.g. `support::Everything`.
+    type BaseCallFilter: Contains<Self, Self::RuntimeCall>;
+}
+
+/// A runtime whose `RuntimeCall` can be dispatched, for pallets which take nested calls as
+/// arguments and dispatch them. This is implemented by `macros::runtime`, which generates the
+/// `RuntimeCall`.
+pub trait RuntimeCallConfig:
+    Config<RuntimeCall: Dispatchable<Self, Origin = Self::RuntimeOrigin> + Clone + core::fmt::Debug>
+    + Sized
+{
+}
+
+/// The origin of a call, in terms of the system pallet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RawOrigin<AccountId> {
+    /// The call has maximum privileges, e.g. it was ordained by the chain itself.
+    Root,
+    /// The call was signed by some account.
+    Signed(AccountId),
+    /// The call was not signed by anybody.
+    None,
+}
+
+// This lets a bare `RawOrigin` be used as a `Config::RuntimeOrigin`, e.g. in tests.
+impl<AccountId> From<RawOrigin<AccountId>> for Result<RawOrigin<AccountId>, RawOrigin<AccountId>> {
+    fn from(origin: RawOrigin<AccountId>) -> Self {
+        Ok(origin)
+    }
+}
+
+/// Ensure that the origin `o` is a signed account, and return that account.
+pub fn ensure_signed<O, AccountId>(o: O) -> Result<AccountId, &'static str>
+where
+    O: Into<Result<RawOrigin<AccountId>, O>>,
+{
+    match o.into() {
+        Ok(RawOrigin::Signed(who)) => Ok(who),
+        _ => Err("bad origin: expected a signed account"),
+    }
+}
+
+/// Ensure that the origin `o` is root.
+pub fn ensure_root<O, AccountId>(o: O) -> Result<(), &'static str>
+where
+    O: Into<Result<RawOrigin<AccountId>, O>>,
+{
+    match o.into() {
+        Ok(RawOrigin::Root) => Ok(()),
+        _ => Err("bad origin: expected root"),
+    }
+}
+
+/// Ensure that the origin `o` is none, i.e. the call was not signed.
+pub fn ensure_none<O, AccountId>(o: O) -> Result<(), &'static str>
+where
+    O: Into<Result<RawOrigin<AccountId>, O>>,
+{
+    match o.into() {
+        Ok(RawOrigin::None) => Ok(()),
+        _ => Err("bad origin: expected none"),
+    }
+}
+
+/// The events emitted by the system pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+    /// A remark was made by `sender`, with the hash of its content.
+    Remarked { sender: T::AccountId, hash: u64 },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T : Config> {
     block_number: T::BlockNumber,
-    nonce: BTreeMap<T::AccountId, T::Nonce>
+    /// The number of extrinsics applied so far in the current block.
+    extrinsic_count: u32,
+    nonce: BTreeMap<T::AccountId, T::Nonce>,
+    storage: BTreeMap<Vec<u8>, Vec<u8>>,
+    events: Vec<Event<T>>,
 }
 
 impl<T: Config> Pallet<T>
 {
 
     pub fn new() -> Self {
-        Self { block_number: T::BlockNumber::zero(), nonce: BTreeMap::new() }
+        Self {
+            block_number: T::BlockNumber::zero(),
+            extrinsic_count: 0,
+            nonce: BTreeMap::new(),
+            storage: BTreeMap::new(),
+            events: Vec::new(),
+        }
     }
 
     pub fn block_number (&self) -> T::BlockNumber{
         self.block_number
     }
 
+    /// Start the next block, which has no extrinsics applied yet.
     pub fn inc_block_number(&mut self) {
         self.block_number += T::BlockNumber::one();
+        self.extrinsic_count = 0;
+    }
+
+    /// The index in the current block of the extrinsic being applied, which is also the number of
+    /// extrinsics applied before it.
+    pub fn extrinsic_index(&self) -> u32 {
+        self.extrinsic_count
+    }
+
+    /// Record that an extrinsic of the current block was applied, whether it succeeded or not.
+    pub fn note_applied_extrinsic(&mut self) {
+        self.extrinsic_count += 1;
     }
 
     pub fn inc_nonce(&mut self, who: &T::AccountId) {
@@ -35,15 +139,99 @@ impl<T: Config> Pallet<T>
         self.nonce.insert(who.clone(), new_nonce);
     }
 
+    /// The number of transactions `who` has made.
+    pub fn nonce(&self, who: &T::AccountId) -> T::Nonce {
+        *self.nonce.get(who).unwrap_or(&T::Nonce::zero())
+    }
+
+    /// Remove everything the system pallet tracks about `who`.
+    pub fn kill_account(&mut self, who: &T::AccountId) {
+        self.nonce.remove(who);
+    }
+
+    /// Read a raw value from storage.
+    pub fn storage(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        self.storage.get(key)
+    }
+
+    /// The events deposited so far.
+    pub fn events(&self) -> &[Event<T>] {
+        &self.events
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+    fn clear_events(&mut self) {
+        self.events.clear();
+    }
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+
+    /// Make some on-chain remark. The content is not stored anywhere.
+    pub fn remark(&mut self, _caller: T::AccountId, remark: Vec<u8>) -> DispatchResult {
+        let _ = remark;
+        Ok(())
+    }
+
+    /// Make some on-chain remark and emit an event with the hash of its content.
+    pub fn remark_with_event(&mut self, caller: T::AccountId, remark: Vec<u8>) -> DispatchResult {
+        let mut hasher = DefaultHasher::new();
+        remark.hash(&mut hasher);
+        self.deposit_event(Event::Remarked { sender: caller, hash: hasher.finish() });
+        Ok(())
+    }
+
+    /// Write some raw `(key, value)` pairs to storage. Root only.
+    pub fn set_storage(
+        &mut self,
+        origin: T::RuntimeOrigin,
+        items: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> DispatchResult {
+        ensure_root(origin)?;
+        for (key, value) in items {
+            self.storage.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// Remove some raw keys from storage. Root only.
+    pub fn kill_storage(&mut self, origin: T::RuntimeOrigin, keys: Vec<Vec<u8>>) -> DispatchResult {
+        ensure_root(origin)?;
+        for key in keys {
+            self.storage.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Remove every raw key starting with `prefix` from storage. Root only.
+    pub fn kill_prefix(&mut self, origin: T::RuntimeOrigin, prefix: Vec<u8>) -> DispatchResult {
+        ensure_root(origin)?;
+        self.storage.retain(|key, _| !key.starts_with(&prefix));
+        Ok(())
+    }
+
 }
 
 #[cfg(test)]
 mod test {
+    use super::RawOrigin;
+
+    #[derive(Debug, PartialEq)]
     struct TestConfig;
     impl super::Config for TestConfig {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeOrigin = RawOrigin<String>;
+        type RuntimeCall = ();
+        type BaseCallFilter = crate::support::Everything;
     }
 	#[test]
 	fn init_system() {
@@ -54,5 +242,193 @@ mod test {
 		assert_eq!(pallet.block_number(), 1);
 		assert_eq!(pallet.nonce.get("alice"), Some(&1));
 		assert_eq!(pallet.nonce.get("bob"), None);
+
+		pallet.note_applied_extrinsic();
+		assert_eq!(pallet.extrinsic_index(), 1);
+		pallet.inc_block_number();
+		assert_eq!(pallet.extrinsic_index(), 0);
+
+		pallet.kill_account(&"alice".to_string());
+		assert_eq!(pallet.nonce.get("alice"), None);
+	}
+
+	#[test]
+	fn remarks() {
+		let mut pallet = super::Pallet::<TestConfig>::new();
+		assert_eq!(pallet.remark("alice".to_string(), b"hello".to_vec()), Ok(()));
+		assert!(pallet.events().is_empty());
+
+		assert_eq!(pallet.remark_with_event("alice".to_string(), b"hello".to_vec()), Ok(()));
+		assert_eq!(pallet.remark_with_event("bob".to_string(), b"hello".to_vec()), Ok(()));
+		let events = pallet.events();
+		assert_eq!(events.len(), 2);
+		match (&events[0], &events[1]) {
+			(
+				super::Event::Remarked { sender: alice, hash: first },
+				super::Event::Remarked { sender: bob, hash: second },
+			) => {
+				assert_eq!(alice, "alice");
+				assert_eq!(bob, "bob");
+				assert_eq!(first, second);
+			},
+		}
+	}
+
+	#[test]
+	fn raw_storage() {
+		let mut pallet = super::Pallet::<TestConfig>::new();
+		let items = vec![
+			(b":code:a".to_vec(), b"1".to_vec()),
+			(b":code:b".to_vec(), b"2".to_vec()),
+			(b":heap".to_vec(), b"3".to_vec()),
+		];
+		assert_eq!(
+			pallet.set_storage(RawOrigin::Signed("alice".to_string()), items.clone()),
+			Err("bad origin: expected root")
+		);
+		assert_eq!(pallet.storage(b":code:a"), None);
+		assert_eq!(pallet.set_storage(RawOrigin::Root, items), Ok(()));
+		assert_eq!(pallet.storage(b":code:a"), Some(&b"1".to_vec()));
+
+		assert_eq!(pallet.kill_storage(RawOrigin::Root, vec![b":heap".to_vec()]), Ok(()));
+		assert_eq!(pallet.storage(b":heap"), None);
+
+		assert_eq!(
+			pallet.kill_prefix(RawOrigin::None, b":code:".to_vec()),
+			Err("bad origin: expected root")
+		);
+		assert_eq!(pallet.kill_prefix(RawOrigin::Root, b":code:".to_vec()), Ok(()));
+		assert_eq!(pallet.storage(b":code:a"), None);
+		assert_eq!(pallet.storage(b":code:b"), None);
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn ensure_origins() {
+		let alice = RawOrigin::Signed("alice".to_string());
+		assert_eq!(super::ensure_signed(alice.clone()), Ok("alice".to_string()));
+		assert_eq!(super::ensure_root(alice.clone()), Err("bad origin: expected root"));
+		assert_eq!(super::ensure_none(alice), Err("bad origin: expected none"));
+
+		assert_eq!(
+			super::ensure_signed::<_, String>(RawOrigin::Root),
+			Err("bad origin: expected a signed account")
+		);
+		assert_eq!(super::ensure_root::<_, String>(RawOrigin::Root), Ok(()));
+		assert_eq!(super::ensure_none::<_, String>(RawOrigin::None), Ok(()));
+	}
+
+	// A pallet with a custom origin: calls approved by a majority of some collective.
+	mod collective {
+		use crate::support::{ClearEvents, DispatchResult};
+		use core::marker::PhantomData;
+
+		pub trait Config:
+			crate::system::Config<RuntimeOrigin: Into<Result<Origin<Self>, Self::RuntimeOrigin>>>
+			+ Sized
+		{
+		}
+
+		/// A call approved by `ayes` of the `of` members of the collective.
+		#[derive(Clone, Debug)]
+		pub struct Origin<T> {
+			pub ayes: u32,
+			pub of: u32,
+			_runtime: PhantomData<T>,
+		}
+
+		impl<T> Origin<T> {
+			pub fn new(ayes: u32, of: u32) -> Self {
+				Self { ayes, of, _runtime: PhantomData }
+			}
+		}
+
+		#[derive(Debug, Clone)]
+		pub struct Pallet<T: Config> {
+			pub motions: u32,
+			_config: PhantomData<T>,
+		}
+
+		impl<T: Config> Pallet<T> {
+			pub fn new() -> Self {
+				Self { motions: 0, _config: PhantomData }
+			}
+		}
+
+		impl<T: Config> ClearEvents for Pallet<T> {
+			fn clear_events(&mut self) {}
+		}
+
+		#[macros::call]
+		impl<T: Config> Pallet<T> {
+			pub fn execute(&mut self, origin: T::RuntimeOrigin) -> DispatchResult {
+				let Ok(Origin { ayes, of, .. }) = origin.into() else {
+					return Err("bad origin: expected the collective");
+				};
+				if ayes * 2 <= of {
+					return Err("motion needs a majority");
+				}
+				self.motions += 1;
+				Ok(())
+			}
+		}
+	}
+
+	mod runtime {
+		use super::collective;
+		use crate::{support::Dispatch, system};
+
+		#[derive(Debug, Clone)]
+		#[macros::runtime]
+		struct TestRuntime {
+			system: system::Pallet<Self>,
+			#[origin]
+			collective: collective::Pallet<Self>,
+		}
+
+		impl system::Config for TestRuntime {
+			type AccountId = String;
+			type BlockNumber = u32;
+			type Nonce = u32;
+			type RuntimeOrigin = RuntimeOrigin;
+			type RuntimeCall = RuntimeCall;
+			type BaseCallFilter = crate::support::Everything;
+		}
+
+		impl collective::Config for TestRuntime {}
+
+		#[test]
+		fn custom_origins() {
+			let mut runtime = TestRuntime::new();
+			let majority: RuntimeOrigin = collective::Origin::new(2, 3).into();
+			let execute = || RuntimeCall::collective(collective::Call::execute {});
+
+			// A custom origin is neither signed nor root.
+			assert_eq!(
+				system::ensure_signed::<_, String>(majority.clone()),
+				Err("bad origin: expected a signed account")
+			);
+			assert_eq!(
+				system::ensure_root::<_, String>(majority.clone()),
+				Err("bad origin: expected root")
+			);
+			let remark = system::Call::remark_with_event { remark: vec![] };
+			assert_eq!(
+				runtime.dispatch(majority.clone(), remark.into()),
+				Err("bad origin: expected a signed account")
+			);
+
+			// Only its own pallet accepts it.
+			assert_eq!(runtime.dispatch(majority, execute()), Ok(()));
+			assert_eq!(runtime.collective.motions, 1);
+			assert_eq!(
+				runtime.dispatch(collective::Origin::new(1, 3).into(), execute()),
+				Err("motion needs a majority")
+			);
+			assert_eq!(
+				runtime.dispatch(RuntimeOrigin::root(), execute()),
+				Err("bad origin: expected the collective")
+			);
+			assert_eq!(runtime.collective.motions, 1);
+		}
+	}
+}
@@ -0,0 +1,299 @@
+use core::fmt::Debug;
+use num::traits::{CheckedAdd, Zero};
+
+use crate::{
+	support::{
+		ClearEvents, DispatchResult, Hooks, InherentData, InherentIdentifier, ProvideInherent, Time,
+	},
+	system::ensure_none,
+};
+
+pub trait Config: crate::system::Config {
+	/// A point in time, e.g. milliseconds since the Unix epoch.
+	type Moment: Zero + CheckedAdd + Copy + Ord + Debug + 'static;
+	/// The least time which must pass between a block and the next one.
+	const MINIMUM_PERIOD: Self::Moment;
+	/// How far ahead of our own time a block made by someone else may set the time.
+	const MAX_DRIFT: Self::Moment;
+}
+
+/// The inherent data with the time of the author of a block, as a `Config::Moment`.
+pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"timstap0";
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The time of the current block, or of the last block which set it.
+	now: T::Moment,
+	/// Whether the time was set in the current block.
+	did_update: bool,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { now: T::Moment::zero(), did_update: false }
+	}
+
+	/// The time of the current block, or zero if it was never set.
+	pub fn now(&self) -> T::Moment {
+		self.now
+	}
+}
+
+// The timestamp pallet deposits no events.
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {}
+}
+
+impl<T: Config> Time for Pallet<T> {
+	type Moment = T::Moment;
+
+	fn now(&self) -> T::Moment {
+		self.now
+	}
+}
+
+impl<T: Config> Hooks<T> for Pallet<T>
+where
+	T: AsMut<Pallet<T>>,
+{
+	// Let the next block set the time again.
+	fn on_finalize(runtime: &mut T) {
+		let timestamp: &mut Self = runtime.as_mut();
+		timestamp.did_update = false;
+	}
+}
+
+// Every block sets the time, from the time of its author.
+impl<T: Config> ProvideInherent for Pallet<T> {
+	type Call = Call<T>;
+
+	const INHERENT_IDENTIFIER: InherentIdentifier = INHERENT_IDENTIFIER;
+
+	fn create_inherent(data: &InherentData) -> Option<Call<T>> {
+		let now = data.get::<T::Moment>(&INHERENT_IDENTIFIER)?;
+		Some(Call::set { now: *now })
+	}
+
+	fn is_inherent(call: &Call<T>) -> bool {
+		matches!(call, Call::set { .. })
+	}
+
+	fn is_inherent_required() -> bool {
+		true
+	}
+
+	fn check_inherent(call: &Call<T>, data: &InherentData) -> DispatchResult {
+		let Call::set { now } = call else { return Ok(()) };
+		let ours = data.get::<T::Moment>(&INHERENT_IDENTIFIER).ok_or("no time to check against")?;
+		if ours.checked_add(&T::MAX_DRIFT).is_some_and(|latest| *now > latest) {
+			return Err("timestamp is too far in the future");
+		}
+		Ok(())
+	}
+}
+
+// Setting the time needs the system pallet, to check where in the block it happens.
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsRef<crate::system::Pallet<T>>,
+{
+	/// Set the time of the current block to `now`.
+	///
+	/// This is an inherent: the block author includes it unsigned, as the first extrinsic of every
+	/// block. The time must be at least `MINIMUM_PERIOD` after the time of the previous block.
+	pub fn set(runtime: &mut T, origin: T::RuntimeOrigin, now: T::Moment) -> DispatchResult {
+		ensure_none(origin)?;
+		let system: &crate::system::Pallet<T> = runtime.as_ref();
+		if system.extrinsic_index() != 0 {
+			return Err("timestamp must be set by the first extrinsic of the block");
+		}
+
+		let timestamp: &mut Self = runtime.as_mut();
+		if timestamp.did_update {
+			return Err("timestamp must be set only once per block");
+		}
+		let earliest = timestamp.now.checked_add(&T::MINIMUM_PERIOD).ok_or("Overflow")?;
+		if !timestamp.now.is_zero() && now < earliest {
+			return Err("timestamp must increase by at least the minimum period");
+		}
+
+		timestamp.now = now;
+		timestamp.did_update = true;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		support::{Block, Dispatch, Extrinsic, Header, Hooks, InherentData, Time},
+		system, timestamp,
+	};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		#[hooks]
+		#[inherent]
+		timestamp: timestamp::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl super::Config for TestRuntime {
+		type Moment = u64;
+		const MINIMUM_PERIOD: u64 = 1_000;
+		const MAX_DRIFT: u64 = 10_000;
+	}
+
+	fn set(now: u64) -> RuntimeCall {
+		super::Call::set { now }.into()
+	}
+
+	// Finish the current block and start the next one, so the time can be set again.
+	fn next_block(runtime: &mut TestRuntime) {
+		runtime.system.inc_block_number();
+		<super::Pallet<TestRuntime> as Hooks<TestRuntime>>::on_finalize(runtime);
+	}
+
+	fn data(now: u64) -> InherentData {
+		let mut data = InherentData::new();
+		data.put(super::INHERENT_IDENTIFIER, now);
+		data
+	}
+
+	type TestExtrinsic = Extrinsic<String, RuntimeCall>;
+	type TestBlock = Block<Header<u32>, TestExtrinsic>;
+
+	// The next block of `runtime`, with `extrinsics`.
+	fn block(runtime: &TestRuntime, extrinsics: Vec<TestExtrinsic>) -> TestBlock {
+		Block { header: Header { block_number: runtime.system.block_number() + 1 }, extrinsics }
+	}
+
+	#[test]
+	fn set_timestamp() {
+		let mut runtime = TestRuntime::new();
+		assert_eq!(runtime.timestamp.now(), 0);
+
+		// The time is set by an unsigned call.
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::signed("alice".to_string()), set(5_000)),
+			Err("bad origin: expected none")
+		);
+		assert_eq!(runtime.dispatch(RuntimeOrigin::none(), set(5_000)), Ok(()));
+		assert_eq!(runtime.timestamp.now(), 5_000);
+		assert_eq!(Time::now(&runtime.timestamp), 5_000);
+
+		// Only once per block.
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::none(), set(10_000)),
+			Err("timestamp must be set only once per block")
+		);
+
+		// The next block must be at least the minimum period later.
+		next_block(&mut runtime);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::none(), set(5_999)),
+			Err("timestamp must increase by at least the minimum period")
+		);
+		assert_eq!(runtime.dispatch(RuntimeOrigin::none(), set(6_000)), Ok(()));
+		assert_eq!(runtime.timestamp.now(), 6_000);
+	}
+
+	#[test]
+	fn timestamp_is_set_first() {
+		let mut runtime = TestRuntime::new();
+		next_block(&mut runtime);
+		runtime.system.note_applied_extrinsic();
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::none(), set(5_000)),
+			Err("timestamp must be set by the first extrinsic of the block")
+		);
+		assert_eq!(runtime.timestamp.now(), 0);
+
+		// A new block starts over.
+		next_block(&mut runtime);
+		assert_eq!(runtime.dispatch(RuntimeOrigin::none(), set(5_000)), Ok(()));
+	}
+
+	#[test]
+	fn blocks_set_the_time_first() {
+		let mut runtime = TestRuntime::new();
+		let remark: RuntimeCall = system::Call::remark_with_event { remark: vec![] }.into();
+		let signed = || Extrinsic::Signed { caller: "alice".to_string(), call: remark.clone() };
+
+		// The author makes the inherent from its time.
+		let inherents = TestRuntime::create_inherents(&data(5_000));
+		assert!(matches!(
+			&inherents[..],
+			[Extrinsic::Inherent { call: RuntimeCall::timestamp(super::Call::set { now: 5_000 }) }]
+		));
+		let mut extrinsics = inherents;
+		extrinsics.push(signed());
+		assert_eq!(runtime.execute_block(block(&runtime, extrinsics)), Ok(()));
+		assert_eq!(runtime.timestamp.now(), 5_000);
+		assert_eq!(runtime.system.nonce(&"alice".to_string()), 1);
+
+		// Blocks without the time, or with inherents after other extrinsics, are rejected.
+		assert_eq!(
+			runtime.execute_block(block(&runtime, vec![signed()])),
+			Err("block is missing a required inherent")
+		);
+		let mut extrinsics = vec![signed()];
+		extrinsics.extend(TestRuntime::create_inherents(&data(10_000)));
+		assert_eq!(
+			runtime.execute_block(block(&runtime, extrinsics)),
+			Err("inherents must come before the other extrinsics")
+		);
+
+		// Only inherents can be put in a block unsigned.
+		let mut extrinsics = TestRuntime::create_inherents(&data(10_000));
+		extrinsics.push(Extrinsic::Inherent { call: remark.clone() });
+		assert_eq!(
+			runtime.execute_block(block(&runtime, extrinsics)),
+			Err("extrinsic is not an inherent")
+		);
+		assert_eq!(runtime.system.block_number(), 1);
+
+		// A block whose inherent fails is invalid.
+		let extrinsics = TestRuntime::create_inherents(&data(5_500));
+		assert_eq!(
+			runtime.execute_block(block(&runtime, extrinsics)),
+			Err("timestamp must increase by at least the minimum period")
+		);
+
+		// It leaves no trace, so the next block can take its place.
+		assert_eq!(runtime.system.block_number(), 1);
+		assert_eq!(runtime.timestamp.now(), 5_000);
+		let extrinsics = TestRuntime::create_inherents(&data(10_000));
+		assert_eq!(runtime.execute_block(block(&runtime, extrinsics)), Ok(()));
+		assert_eq!(runtime.system.block_number(), 2);
+		assert_eq!(runtime.timestamp.now(), 10_000);
+	}
+
+	#[test]
+	fn imported_blocks_are_checked_against_our_time() {
+		let mut runtime = TestRuntime::new();
+
+		// Someone else's clock is too far ahead of ours.
+		let theirs = block(&runtime, TestRuntime::create_inherents(&data(20_001)));
+		assert_eq!(
+			runtime.import_block(theirs, &data(10_000)),
+			Err("timestamp is too far in the future")
+		);
+		assert_eq!(runtime.system.block_number(), 0);
+
+		let theirs = block(&runtime, TestRuntime::create_inherents(&data(20_000)));
+		assert_eq!(runtime.import_block(theirs, &data(10_000)), Ok(()));
+		assert_eq!(runtime.timestamp.now(), 20_000);
+	}
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeSet;
+
+use crate::{
+	support::{ClearEvents, Contains, DispatchResult, GetCallMetadata},
+	system::ensure_root,
+};
+
+/// The calls of a pallet which are paused: every call of the pallet `.0` if `.1` is `None`, or
+/// only its call `.1` otherwise. These are the names the runtime gives its calls.
+pub type PausedName = (&'static str, Option<&'static str>);
+
+/// Calls are paused by the names `support::GetCallMetadata` gives them.
+pub trait Config: crate::system::RuntimeCallConfig<RuntimeCall: GetCallMetadata> {
+	/// The pallets whose calls can never be paused, e.g. `sudo` and this pallet itself, so that
+	/// calls can always be unpaused again, and pallets with inherents, so blocks stay valid.
+	const WHITELISTED_PALLETS: &'static [&'static str];
+}
+
+/// The events emitted by the tx-pause pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+	/// The calls named `name` were paused.
+	CallPaused { name: PausedName },
+	/// The calls named `name` were unpaused.
+	CallUnpaused { name: PausedName },
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The names of the calls which are paused.
+	paused: BTreeSet<PausedName>,
+	events: Vec<Event>,
+	_config: core::marker::PhantomData<T>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { paused: BTreeSet::new(), events: Vec::new(), _config: core::marker::PhantomData }
+	}
+
+	/// Whether `call` is paused, on its own or along with the rest of its pallet.
+	pub fn is_paused(&self, call: &T::RuntimeCall) -> bool {
+		let pallet_name = call.pallet_name();
+		if T::WHITELISTED_PALLETS.contains(&pallet_name) {
+			return false;
+		}
+		self.paused.contains(&(pallet_name, None))
+			|| self.paused.contains(&(pallet_name, Some(call.call_name())))
+	}
+
+	/// The names of the calls which are paused.
+	pub fn paused(&self) -> impl Iterator<Item = &PausedName> {
+		self.paused.iter()
+	}
+
+	/// The events deposited so far.
+	pub fn events(&self) -> &[Event] {
+		&self.events
+	}
+
+	fn deposit_event(&mut self, event: Event) {
+		self.events.push(event);
+	}
+
+	// The calls named `pallet_name` and `call_name`, with the names the runtime gives them.
+	fn paused_name(pallet_name: &str, call_name: Option<&str>) -> Result<PausedName, &'static str> {
+		let pallet_name = T::RuntimeCall::pallet_names()
+			.iter()
+			.find(|name| **name == pallet_name)
+			.ok_or("pallet does not exist")?;
+		let Some(call_name) = call_name else { return Ok((pallet_name, None)) };
+		let call_name = T::RuntimeCall::call_names(pallet_name)
+			.iter()
+			.find(|name| **name == call_name)
+			.ok_or("call does not exist")?;
+		Ok((pallet_name, Some(call_name)))
+	}
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+// This is meant to be the `BaseCallFilter` of the runtime, so it lets through the calls which are
+// not paused.
+impl<T: Config> Contains<T, T::RuntimeCall> for Pallet<T>
+where
+	T: AsRef<Pallet<T>>,
+{
+	fn contains(runtime: &T, call: &T::RuntimeCall) -> bool {
+		let pallet: &Self = runtime.as_ref();
+		!pallet.is_paused(call)
+	}
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Pause the call `call_name` of the pallet `pallet_name`, or every call of the pallet if no
+	/// call is named, until it is unpaused. The names must be ones the runtime gives its calls.
+	/// Only root may do this.
+	pub fn pause(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		pallet_name: String,
+		call_name: Option<String>,
+	) -> DispatchResult {
+		ensure_root(origin)?;
+		let name = Self::paused_name(&pallet_name, call_name.as_deref())?;
+		if T::WHITELISTED_PALLETS.contains(&name.0) {
+			return Err("calls of this pallet can not be paused");
+		}
+		if !self.paused.insert(name) {
+			return Err("calls are already paused");
+		}
+		self.deposit_event(Event::CallPaused { name });
+		Ok(())
+	}
+
+	/// Unpause calls paused by `pause`, with the same names. Only root may do this.
+	pub fn unpause(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		pallet_name: String,
+		call_name: Option<String>,
+	) -> DispatchResult {
+		ensure_root(origin)?;
+		let name = Self::paused_name(&pallet_name, call_name.as_deref())?;
+		if !self.paused.remove(&name) {
+			return Err("calls are not paused");
+		}
+		self.deposit_event(Event::CallUnpaused { name });
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Event;
+	use crate::{balances, sudo, support::Dispatch, system, tx_pause, utility};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		sudo: sudo::Pallet<Self>,
+		utility: utility::Pallet<Self>,
+		tx_pause: tx_pause::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = tx_pause::Pallet<Self>;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	impl sudo::Config for TestRuntime {}
+
+	impl utility::Config for TestRuntime {}
+
+	impl super::Config for TestRuntime {
+		const WHITELISTED_PALLETS: &'static [&'static str] = &["sudo", "tx_pause"];
+	}
+
+	fn setup() -> TestRuntime {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime.sudo.initialize_key("alice".to_string());
+		runtime
+	}
+
+	crate::support::test_helpers!();
+
+	fn pause(pallet_name: &str, call_name: Option<&str>) -> RuntimeCall {
+		let pallet_name = pallet_name.to_string();
+		let call_name = call_name.map(str::to_string);
+		super::Call::pause { pallet_name, call_name }.into()
+	}
+
+	fn unpause(pallet_name: &str, call_name: Option<&str>) -> RuntimeCall {
+		let pallet_name = pallet_name.to_string();
+		let call_name = call_name.map(str::to_string);
+		super::Call::unpause { pallet_name, call_name }.into()
+	}
+
+	#[test]
+	fn paused_calls_are_filtered() {
+		let mut runtime = setup();
+		let alice = "alice".to_string();
+
+		assert_eq!(
+			runtime.dispatch(signed("alice"), pause("balances", Some("transfer"))),
+			Err("bad origin: expected root")
+		);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::root(), pause("balances", Some("transfer"))),
+			Ok(())
+		);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::root(), pause("balances", Some("transfer"))),
+			Err("calls are already paused")
+		);
+		assert!(matches!(runtime.tx_pause.events(), [Event::CallPaused { .. }]));
+
+		// The paused call is filtered, even when nested in another call, but the rest of the
+		// pallet is not.
+		assert_eq!(runtime.dispatch(signed("alice"), transfer("bob", 10)), Err("call is filtered"));
+		let batch = utility::Call::batch_all { calls: vec![transfer("bob", 10)] };
+		assert_eq!(runtime.dispatch(signed("alice"), batch.into()), Err("call is filtered"));
+		let keep_alive = balances::Call::transfer_keep_alive { to: "bob".to_string(), amount: 10 };
+		assert_eq!(runtime.dispatch(signed("alice"), keep_alive.into()), Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 90);
+
+		// Sudo can still unpause it.
+		let sudo = sudo::Call::sudo { call: Box::new(unpause("balances", Some("transfer"))) };
+		assert_eq!(runtime.dispatch(signed("alice"), sudo.into()), Ok(()));
+		assert!(runtime.tx_pause.paused().next().is_none());
+		assert_eq!(runtime.dispatch(signed("alice"), transfer("bob", 10)), Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 80);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::root(), unpause("balances", Some("transfer"))),
+			Err("calls are not paused")
+		);
+	}
+
+	#[test]
+	fn pause_whole_pallets() {
+		let mut runtime = setup();
+
+		assert_eq!(runtime.dispatch(RuntimeOrigin::root(), pause("balances", None)), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), transfer("bob", 10)), Err("call is filtered"));
+		let keep_alive = balances::Call::transfer_keep_alive { to: "bob".to_string(), amount: 10 };
+		assert_eq!(runtime.dispatch(signed("alice"), keep_alive.into()), Err("call is filtered"));
+
+		// Root is not filtered, so sudo can still fix an account while the pallet is paused.
+		let force = balances::Call::force_set_balance { who: "bob".to_string(), amount: 5 };
+		let sudo = sudo::Call::sudo { call: Box::new(force.into()) };
+		assert_eq!(runtime.dispatch(signed("alice"), sudo.into()), Ok(()));
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 5);
+
+		// Only the calls the runtime has can be paused.
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::root(), pause("balance", None)),
+			Err("pallet does not exist")
+		);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::root(), pause("balances", Some("transfer_all"))),
+			Err("call does not exist")
+		);
+		assert_eq!(runtime.tx_pause.paused().count(), 1);
+
+		// Whitelisted pallets can not be paused.
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::root(), pause("tx_pause", Some("unpause"))),
+			Err("calls of this pallet can not be paused")
+		);
+		assert_eq!(
+			runtime.dispatch(RuntimeOrigin::root(), pause("sudo", None)),
+			Err("calls of this pallet can not be paused")
+		);
+
+		assert_eq!(runtime.dispatch(RuntimeOrigin::root(), unpause("balances", None)), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), transfer("bob", 10)), Ok(()));
+	}
+}
@@ -0,0 +1,277 @@
+use crate::support::{self, ClearEvents, DispatchResult, Dispatchable};
+
+pub trait Config: crate::system::RuntimeCallConfig {}
+
+/// The events emitted by the utility pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+	/// A call in a batch failed with `error`, so the calls after `index` were not dispatched.
+	BatchInterrupted { index: usize, error: &'static str },
+	/// Every call in a batch was dispatched successfully.
+	BatchCompleted,
+	/// Every call in a batch was dispatched, but some of them failed.
+	BatchCompletedWithErrors,
+	/// A call in a batch was dispatched successfully.
+	ItemCompleted,
+	/// A call in a batch failed with `error`.
+	ItemFailed { error: &'static str },
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	events: Vec<Event>,
+	_config: core::marker::PhantomData<T>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { events: Vec::new(), _config: core::marker::PhantomData }
+	}
+
+	/// The events deposited so far.
+	pub fn events(&self) -> &[Event] {
+		&self.events
+	}
+
+	fn deposit_event(&mut self, event: Event) {
+		self.events.push(event);
+	}
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + Clone,
+{
+	/// Dispatch `calls` one after the other with the same origin, stopping at the first one which
+	/// fails. The calls before it keep their effects.
+	///
+	/// This never fails itself. How far the batch got, and the error it stopped at, are only
+	/// reported in the events.
+	pub fn batch(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		calls: Vec<T::RuntimeCall>,
+	) -> DispatchResult {
+		for (index, call) in calls.into_iter().enumerate() {
+			match call.dispatch(runtime, origin.clone()) {
+				Ok(()) => AsMut::<Self>::as_mut(runtime).deposit_event(Event::ItemCompleted),
+				Err(error) => {
+					AsMut::<Self>::as_mut(runtime)
+						.deposit_event(Event::BatchInterrupted { index, error });
+					return Ok(());
+				},
+			}
+		}
+		AsMut::<Self>::as_mut(runtime).deposit_event(Event::BatchCompleted);
+		Ok(())
+	}
+
+	/// Dispatch `calls` one after the other with the same origin, rolling all of them back if any
+	/// of them fails.
+	pub fn batch_all(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		calls: Vec<T::RuntimeCall>,
+	) -> DispatchResult {
+		support::with_transaction(runtime, |runtime| {
+			for call in calls {
+				call.dispatch(runtime, origin.clone())?;
+				AsMut::<Self>::as_mut(runtime).deposit_event(Event::ItemCompleted);
+			}
+			Ok(())
+		})?;
+		AsMut::<Self>::as_mut(runtime).deposit_event(Event::BatchCompleted);
+		Ok(())
+	}
+
+	/// Dispatch `calls` one after the other with the same origin, carrying on when some of them
+	/// fail. The calls which succeed keep their effects.
+	pub fn force_batch(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		calls: Vec<T::RuntimeCall>,
+	) -> DispatchResult {
+		let mut failed = false;
+		for call in calls {
+			let event = match call.dispatch(runtime, origin.clone()) {
+				Ok(()) => Event::ItemCompleted,
+				Err(error) => {
+					failed = true;
+					Event::ItemFailed { error }
+				},
+			};
+			AsMut::<Self>::as_mut(runtime).deposit_event(event);
+		}
+		let event = if failed { Event::BatchCompletedWithErrors } else { Event::BatchCompleted };
+		AsMut::<Self>::as_mut(runtime).deposit_event(event);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::Event;
+	use crate::{
+		balances,
+		support::{Block, Dispatch, Extrinsic, Header},
+		system, utility,
+	};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		utility: utility::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	impl super::Config for TestRuntime {}
+
+	crate::support::test_helpers!();
+
+	fn runtime() -> TestRuntime {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime
+	}
+
+	#[test]
+	fn batch_stops_at_first_error() {
+		let mut runtime = runtime();
+		let calls = vec![transfer("bob", 10), transfer("bob", 200), transfer("charlie", 10)];
+		assert_eq!(
+			runtime.dispatch(signed("alice"), RuntimeCall::utility(super::Call::batch { calls })),
+			Ok(())
+		);
+
+		assert_eq!(runtime.balances.balance(&"alice".to_string()), 90);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 10);
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 0);
+		assert_eq!(
+			runtime.utility.events(),
+			&[
+				Event::ItemCompleted,
+				Event::BatchInterrupted { index: 1, error: "Not enough funds." },
+			]
+		);
+	}
+
+	#[test]
+	fn batch_all_rolls_back_on_error() {
+		let mut runtime = runtime();
+		let calls = vec![transfer("bob", 10), transfer("bob", 200)];
+		assert_eq!(
+			runtime
+				.dispatch(signed("alice"), RuntimeCall::utility(super::Call::batch_all { calls })),
+			Err("Not enough funds.")
+		);
+		assert_eq!(runtime.balances.balance(&"alice".to_string()), 100);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 0);
+		assert!(runtime.utility.events().is_empty());
+
+		let calls = vec![transfer("bob", 10), transfer("charlie", 20)];
+		assert_eq!(
+			runtime
+				.dispatch(signed("alice"), RuntimeCall::utility(super::Call::batch_all { calls })),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&"alice".to_string()), 70);
+		assert_eq!(
+			runtime.utility.events(),
+			&[Event::ItemCompleted, Event::ItemCompleted, Event::BatchCompleted]
+		);
+	}
+
+	#[test]
+	fn force_batch_continues_on_error() {
+		let mut runtime = runtime();
+		let calls = vec![transfer("bob", 10), transfer("bob", 200), transfer("charlie", 10)];
+		assert_eq!(
+			runtime.dispatch(
+				signed("alice"),
+				RuntimeCall::utility(super::Call::force_batch { calls })
+			),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&"alice".to_string()), 80);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 10);
+		assert_eq!(runtime.balances.balance(&"charlie".to_string()), 10);
+		assert_eq!(
+			runtime.utility.events(),
+			&[
+				Event::ItemCompleted,
+				Event::ItemFailed { error: "Not enough funds." },
+				Event::ItemCompleted,
+				Event::BatchCompletedWithErrors,
+			]
+		);
+	}
+
+	#[test]
+	fn batch_keeps_the_origin() {
+		let mut runtime = runtime();
+		let calls = || {
+			vec![RuntimeCall::balances(balances::Call::force_set_balance {
+				who: "bob".to_string(),
+				amount: 5,
+			})]
+		};
+		assert_eq!(
+			runtime.dispatch(
+				signed("alice"),
+				RuntimeCall::utility(super::Call::batch_all { calls: calls() })
+			),
+			Err("bad origin: expected root")
+		);
+		assert_eq!(
+			runtime.dispatch(
+				RuntimeOrigin::root(),
+				RuntimeCall::utility(super::Call::batch_all { calls: calls() }),
+			),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 5);
+	}
+
+	#[test]
+	fn events_only_describe_the_current_block() {
+		let mut runtime = runtime();
+		let remark = system::Call::remark_with_event { remark: b"hello".to_vec() };
+		let calls = vec![transfer("bob", 10), remark.into()];
+		let batch = Extrinsic::Signed {
+			caller: "alice".to_string(),
+			call: super::Call::batch { calls }.into(),
+		};
+		let block = Block { header: Header { block_number: 1 }, extrinsics: vec![batch] };
+		assert_eq!(runtime.execute_block(block), Ok(()));
+		assert_eq!(runtime.utility.events().len(), 3);
+		assert_eq!(runtime.system.events().len(), 1);
+
+		// Every pallet starts the next block without events.
+		let block = Block { header: Header { block_number: 2 }, extrinsics: vec![] };
+		assert_eq!(runtime.execute_block(block), Ok(()));
+		assert!(runtime.utility.events().is_empty());
+		assert!(runtime.system.events().is_empty());
+	}
+}
@@ -0,0 +1,364 @@
+use core::fmt::Debug;
+use num::traits::{CheckedMul, Saturating, Zero};
+use std::collections::BTreeMap;
+
+use crate::support::{
+	ClearEvents, Currency, DispatchResult, Encode, ExistenceRequirement, LockIdentifier,
+	LockableCurrency, WithdrawReasons,
+};
+
+/// The name of the lock vesting puts on the funds which are not vested yet.
+pub const VESTING_ID: LockIdentifier = *b"vesting ";
+
+pub trait Config: crate::system::Config {
+	/// The balance type of `Currency`, with the arithmetic vesting needs. Block numbers turn into
+	/// balances to work out how much has vested.
+	type Balance: Zero
+		+ Saturating
+		+ CheckedMul
+		+ From<Self::BlockNumber>
+		+ Copy
+		+ PartialOrd
+		+ Debug;
+	/// The currency which vests, usually the `balances` pallet.
+	type Currency: LockableCurrency<Self::AccountId, Balance = Self::Balance>;
+	/// The least amount `vested_transfer` can vest.
+	const MIN_VESTED_TRANSFER: Self::Balance;
+	/// The maximum number of vesting schedules an account can have.
+	const MAX_VESTING_SCHEDULES: u32;
+}
+
+/// The events emitted by the vesting pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T: Config> {
+	/// The lock on `account` was updated, and `unvested` is still locked.
+	VestingUpdated { account: T::AccountId, unvested: T::Balance },
+	/// Everything `account` had vesting is unlocked.
+	VestingCompleted { account: T::AccountId },
+}
+
+/// Some funds which unlock a bit with every block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VestingInfo<Balance, BlockNumber> {
+	/// The amount locked at first.
+	pub locked: Balance,
+	/// How much of it unlocks with every block after `starting_block`.
+	pub per_block: Balance,
+	/// The block after which funds start to unlock.
+	pub starting_block: BlockNumber,
+}
+
+impl<Balance: Encode, BlockNumber: Encode> Encode for VestingInfo<Balance, BlockNumber> {
+	fn encode_to(&self, dest: &mut Vec<u8>) {
+		self.locked.encode_to(dest);
+		self.per_block.encode_to(dest);
+		self.starting_block.encode_to(dest);
+	}
+}
+
+/// The vesting schedules of a given runtime.
+pub type VestingInfoOf<T> =
+	VestingInfo<<T as Config>::Balance, <T as crate::system::Config>::BlockNumber>;
+
+impl<Balance, BlockNumber> VestingInfo<Balance, BlockNumber>
+where
+	Balance: Zero + Saturating + CheckedMul + From<BlockNumber> + Copy,
+	BlockNumber: Copy + Ord,
+{
+	/// How much of the funds are still locked in the block `now`.
+	pub fn locked_at(&self, now: BlockNumber) -> Balance {
+		if now <= self.starting_block {
+			return self.locked;
+		}
+		let elapsed = Balance::from(now).saturating_sub(Balance::from(self.starting_block));
+		match self.per_block.checked_mul(&elapsed) {
+			Some(vested) => self.locked.saturating_sub(vested),
+			None => Balance::zero(),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The vesting schedules of each account.
+	#[allow(clippy::type_complexity)]
+	vesting: BTreeMap<T::AccountId, Vec<VestingInfo<T::Balance, T::BlockNumber>>>,
+	events: Vec<Event<T>>,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn new() -> Self {
+		Self { vesting: BTreeMap::new(), events: Vec::new() }
+	}
+
+	/// The vesting schedules of `who`.
+	pub fn vesting(&self, who: &T::AccountId) -> &[VestingInfoOf<T>] {
+		self.vesting.get(who).map(Vec::as_slice).unwrap_or_default()
+	}
+
+	/// How much of the funds of `who` are still locked in the block `now`.
+	pub fn vesting_balance(&self, who: &T::AccountId, now: T::BlockNumber) -> T::Balance {
+		self.vesting(who).iter().fold(T::Balance::zero(), |total, schedule| {
+			total.saturating_add(schedule.locked_at(now))
+		})
+	}
+
+	/// The events deposited so far.
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+
+	// Ensure that `schedule` makes sense, and that `who` can have one more.
+	fn ensure_can_add(&self, who: &T::AccountId, schedule: &VestingInfoOf<T>) -> DispatchResult {
+		if schedule.locked.is_zero() || schedule.per_block.is_zero() {
+			return Err("vesting schedule must lock and unlock something");
+		}
+		if self.vesting(who).len() >= T::MAX_VESTING_SCHEDULES as usize {
+			return Err("account has too many vesting schedules");
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> ClearEvents for Pallet<T> {
+	fn clear_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>> + AsMut<T::Currency> + AsRef<crate::system::Pallet<T>>,
+{
+	/// Add a vesting schedule over funds `who` already has, and lock them. This is how schedules
+	/// are set up when the chain starts.
+	pub fn add_vesting_schedule(
+		runtime: &mut T,
+		who: &T::AccountId,
+		schedule: VestingInfoOf<T>,
+	) -> DispatchResult {
+		let vesting: &mut Self = runtime.as_mut();
+		vesting.ensure_can_add(who, &schedule)?;
+		vesting.vesting.entry(who.clone()).or_default().push(schedule);
+		Self::update_lock(runtime, who);
+		Ok(())
+	}
+
+	// Lock what is still vesting for `who`, and forget the schedules which are done.
+	fn update_lock(runtime: &mut T, who: &T::AccountId) {
+		let system: &crate::system::Pallet<T> = runtime.as_ref();
+		let now = system.block_number();
+		let vesting: &mut Self = runtime.as_mut();
+		let unvested = vesting.vesting_balance(who, now);
+		if let Some(schedules) = vesting.vesting.get_mut(who) {
+			schedules.retain(|schedule| !schedule.locked_at(now).is_zero());
+			if schedules.is_empty() {
+				vesting.vesting.remove(who);
+			}
+		}
+
+		let event = if unvested.is_zero() {
+			Event::VestingCompleted { account: who.clone() }
+		} else {
+			Event::VestingUpdated { account: who.clone(), unvested }
+		};
+		vesting.deposit_event(event);
+		let currency: &mut T::Currency = runtime.as_mut();
+		currency.set_lock(VESTING_ID, who, unvested, WithdrawReasons::ALL);
+	}
+
+	fn do_vest(runtime: &mut T, who: &T::AccountId) -> DispatchResult {
+		let vesting: &Self = runtime.as_mut();
+		if vesting.vesting(who).is_empty() {
+			return Err("account has no vesting schedule");
+		}
+		Self::update_lock(runtime, who);
+		Ok(())
+	}
+}
+
+// Vesting reaches the currency through `T::Currency` to lock funds, and the system pallet to learn
+// the current block and to reap the accounts which transfer all of their funds.
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: AsMut<Pallet<T>>
+		+ AsMut<T::Currency>
+		+ AsRef<crate::system::Pallet<T>>
+		+ AsMut<crate::system::Pallet<T>>,
+{
+	/// Unlock the funds of the caller which vested by now.
+	pub fn vest(runtime: &mut T, caller: T::AccountId) -> DispatchResult {
+		Self::do_vest(runtime, &caller)
+	}
+
+	/// Unlock the funds of `target` which vested by now.
+	pub fn vest_other(
+		runtime: &mut T,
+		_caller: T::AccountId,
+		target: T::AccountId,
+	) -> DispatchResult {
+		Self::do_vest(runtime, &target)
+	}
+
+	/// Transfer `schedule.locked` to `target`, and have it vest there with `schedule`.
+	pub fn vested_transfer(
+		runtime: &mut T,
+		caller: T::AccountId,
+		target: T::AccountId,
+		schedule: VestingInfo<T::Balance, T::BlockNumber>,
+	) -> DispatchResult {
+		if schedule.locked < T::MIN_VESTED_TRANSFER {
+			return Err("amount vested is too low");
+		}
+		let vesting: &Self = runtime.as_mut();
+		vesting.ensure_can_add(&target, &schedule)?;
+
+		let currency: &mut T::Currency = runtime.as_mut();
+		currency.transfer(&caller, &target, schedule.locked, ExistenceRequirement::AllowDeath)?;
+		if !currency.account_exists(&caller) {
+			let system: &mut crate::system::Pallet<T> = runtime.as_mut();
+			system.kill_account(&caller);
+		}
+		Self::add_vesting_schedule(runtime, &target, schedule)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Event, VestingInfo};
+	use crate::{balances, support::Dispatch, system, vesting};
+
+	#[derive(Debug, Clone)]
+	#[macros::runtime]
+	struct TestRuntime {
+		system: system::Pallet<Self>,
+		balances: balances::Pallet<Self>,
+		vesting: vesting::Pallet<Self>,
+	}
+
+	impl system::Config for TestRuntime {
+		type AccountId = String;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeOrigin = RuntimeOrigin;
+		type RuntimeCall = RuntimeCall;
+		type BaseCallFilter = crate::support::Everything;
+	}
+
+	impl balances::Config for TestRuntime {
+		type Balance = u128;
+		const EXISTENTIAL_DEPOSIT: u128 = 1;
+	}
+
+	impl super::Config for TestRuntime {
+		type Balance = u128;
+		type Currency = balances::Pallet<Self>;
+		const MIN_VESTED_TRANSFER: u128 = 10;
+		const MAX_VESTING_SCHEDULES: u32 = 2;
+	}
+
+	crate::support::test_helpers!();
+
+	fn vested_transfer(target: &str, locked: u128, per_block: u128) -> RuntimeCall {
+		let schedule = VestingInfo { locked, per_block, starting_block: 2 };
+		super::Call::vested_transfer { target: target.to_string(), schedule }.into()
+	}
+
+	#[test]
+	fn funds_vest_linearly() {
+		let mut runtime = TestRuntime::new();
+		let bob = "bob".to_string();
+		runtime.balances.set_balance(&"alice".to_string(), 1_000);
+
+		assert_eq!(runtime.dispatch(signed("alice"), vested_transfer("bob", 100, 10)), Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 100);
+		assert_eq!(runtime.vesting.vesting_balance(&bob, 0), 100);
+		assert_eq!(
+			runtime.dispatch(signed("bob"), transfer("charlie", 1)),
+			Err("funds are locked")
+		);
+
+		// Nothing unlocks until the starting block, and then 10 per block.
+		run_to_block(&mut runtime, 2);
+		assert_eq!(runtime.vesting.vesting_balance(&bob, 2), 100);
+		run_to_block(&mut runtime, 5);
+		assert_eq!(
+			runtime.dispatch(
+				signed("charlie"),
+				super::Call::vest_other { target: bob.clone() }.into()
+			),
+			Ok(())
+		);
+		assert!(matches!(
+			runtime.vesting.events().last(),
+			Some(Event::VestingUpdated { account, unvested: 70 }) if account == "bob"
+		));
+		assert_eq!(runtime.dispatch(signed("bob"), transfer("charlie", 30)), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("bob"), transfer("charlie", 1)),
+			Err("funds are locked")
+		);
+
+		// Once everything vested, the lock and the schedule are gone.
+		run_to_block(&mut runtime, 12);
+		assert_eq!(runtime.dispatch(signed("bob"), super::Call::vest {}.into()), Ok(()));
+		assert!(matches!(
+			runtime.vesting.events().last(),
+			Some(Event::VestingCompleted { account }) if account == "bob"
+		));
+		assert!(runtime.vesting.vesting(&bob).is_empty());
+		assert!(runtime.balances.locks(&bob).is_empty());
+		assert_eq!(runtime.dispatch(signed("bob"), transfer("charlie", 70)), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("bob"), super::Call::vest {}.into()),
+			Err("account has no vesting schedule")
+		);
+	}
+
+	#[test]
+	fn vested_transfer_checks() {
+		let mut runtime = TestRuntime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 1_000);
+
+		assert_eq!(
+			runtime.dispatch(signed("alice"), vested_transfer("bob", 9, 1)),
+			Err("amount vested is too low")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), vested_transfer("bob", 10, 0)),
+			Err("vesting schedule must lock and unlock something")
+		);
+		assert_eq!(
+			runtime.dispatch(signed("alice"), vested_transfer("bob", 2_000, 1)),
+			Err("Not enough funds.")
+		);
+
+		// Schedules add up, up to the limit.
+		assert_eq!(runtime.dispatch(signed("alice"), vested_transfer("bob", 10, 1)), Ok(()));
+		assert_eq!(runtime.dispatch(signed("alice"), vested_transfer("bob", 20, 1)), Ok(()));
+		assert_eq!(
+			runtime.dispatch(signed("alice"), vested_transfer("bob", 30, 1)),
+			Err("account has too many vesting schedules")
+		);
+		assert_eq!(runtime.vesting.vesting_balance(&"bob".to_string(), 4), 26);
+		assert_eq!(runtime.balances.balance(&"alice".to_string()), 970);
+	}
+
+	#[test]
+	fn vesting_away_the_whole_balance_reaps_the_caller() {
+		let mut runtime = TestRuntime::new();
+		let alice = "alice".to_string();
+		runtime.balances.set_balance(&alice, 100);
+		runtime.system.inc_nonce(&alice);
+
+		assert_eq!(runtime.dispatch(signed("alice"), vested_transfer("bob", 100, 10)), Ok(()));
+		assert_eq!(runtime.balances.total_balance(&alice), 0);
+		assert_eq!(runtime.system.nonce(&alice), 0);
+		assert_eq!(runtime.balances.balance(&"bob".to_string()), 100);
+	}
+}